@@ -82,7 +82,8 @@ mod shaving {
 }
 
 fn main() {
-    serif::Config::new()
+    // hold the guard until the end of main, so that output is flushed
+    let _guard = serif::Config::new()
         // set trace level by default to show all the features
         .with_default(tracing::Level::TRACE)
         // these options are the defaults, but are included here for completeness
@@ -0,0 +1,95 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Command line flags for configuring serif, enabled by the `clap` feature.
+//!
+//! [`Args`] can be flattened into an application's own clap [`Parser`](clap::Parser), then
+//! passed to [`Config::from_args`] or [`Config::apply_args`].
+//!
+//! ```
+//! use clap::Parser;
+//!
+//! #[derive(Parser)]
+//! struct Cli {
+//!     #[command(flatten)]
+//!     log: serif::cli::Args,
+//!     // other application arguments
+//! }
+//!
+//! let cli = Cli::parse_from(["myapp", "-vv", "--color=never"]);
+//! assert_eq!(cli.log.verbosity(), 2);
+//! let config = serif::Config::from_args(&cli.log);
+//! # let _ = config;
+//! ```
+
+use std::path::PathBuf;
+
+use crate::{ColorMode, Config, Format, Output};
+
+/// Common logging flags, to be flattened into a clap [`Parser`](clap::Parser).
+///
+/// The flags are:
+///   * `-v`/`--verbose` and `-q`/`--quiet`, which can be repeated to increase or decrease the
+///     default log level. See [`Config::with_verbosity`] for the mapping of levels.
+///   * `--color <auto|always|never>`, see [`ColorMode`].
+///   * `--log-file <PATH>`, to append logs to a file instead of writing them to stdout.
+///   * `--log-format <full|compact>`, see [`Format`].
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Args {
+    /// Increase log verbosity, can be repeated
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Decrease log verbosity, can be repeated
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub quiet: u8,
+
+    /// When to use colors in log output
+    #[arg(long, value_name = "WHEN", value_enum, global = true)]
+    pub color: Option<ColorMode>,
+
+    /// Append log output to a file
+    #[arg(long, value_name = "PATH", global = true)]
+    pub log_file: Option<PathBuf>,
+
+    /// Log output format
+    #[arg(long, value_name = "FORMAT", value_enum, global = true)]
+    pub log_format: Option<Format>,
+}
+
+impl Args {
+    /// The net verbosity, the number of `-v` flags minus the number of `-q` flags.
+    pub fn verbosity(&self) -> i32 {
+        i32::from(self.verbose) - i32::from(self.quiet)
+    }
+}
+
+impl Config {
+    /// Create a new `Config` with the default configuration, then apply command line flags. See
+    /// [`apply_args`](Config::apply_args) for details.
+    pub fn from_args(args: &Args) -> Self {
+        Self::new().apply_args(args)
+    }
+
+    /// Override this Config's options with command line flags.
+    ///
+    /// Only options which were given on the command line are changed. `--log-format` only changes
+    /// the options set by the format preset (see [`with_format`](Config::with_format)). If any
+    /// `-v` or `-q` flags were given, the default level is set using
+    /// [`with_verbosity`](Config::with_verbosity), replacing any previous default.
+    pub fn apply_args(self, args: &Args) -> Self {
+        let mut config = self;
+        if let Some(format) = args.log_format {
+            config = config.with_format(format);
+        }
+        if args.verbose != 0 || args.quiet != 0 {
+            config = config.with_verbosity(args.verbosity());
+        }
+        if let Some(color) = args.color {
+            config = config.with_color(color);
+        }
+        if let Some(path) = &args.log_file {
+            config = config.with_output(Output::File(path.clone()));
+        }
+        config
+    }
+}
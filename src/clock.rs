@@ -0,0 +1,137 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Time sources for timestamps. This module is private, but its pub types are exported and inlined
+//! at the top-level of the `serif` crate.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use jiff::Timestamp;
+
+/// A source of the current time, used to render timestamps.
+///
+/// [`EventFormatter`](crate::EventFormatter) uses [`SystemClock`] by default. Other clocks are
+/// useful for tests that need predictable timestamps, or for replaying recorded events.
+///
+/// `Clock` is implemented for closures which return a [`Timestamp`], so
+/// `EventFormatter::new().with_clock(|| my_timestamp)` works too.
+pub trait Clock: Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> Timestamp;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> Timestamp + Send + Sync,
+{
+    fn now(&self) -> Timestamp {
+        self()
+    }
+}
+
+/// The system clock, using [`Timestamp::now`]. This is the default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+/// A clock that always returns the same time.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(Timestamp);
+
+impl FixedClock {
+    /// Create a clock that always returns `time`.
+    pub const fn new(time: Timestamp) -> Self {
+        Self(time)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Timestamp {
+        self.0
+    }
+}
+
+/// A clock that only changes when it's told to.
+///
+/// Clones of a `ManualClock` share the same time, so one clone can be given to an
+/// [`EventFormatter`](crate::EventFormatter) while another is used to advance it.
+///
+/// ```
+/// use serif::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new("2024-01-01T00:00:00Z".parse().unwrap());
+/// let formatter_clock = clock.clone();
+/// clock.advance(Duration::from_secs(90));
+/// assert_eq!(formatter_clock.now().to_string(), "2024-01-01T00:01:30Z");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock(Arc<Mutex<Timestamp>>);
+
+impl ManualClock {
+    /// Create a clock starting at `time`.
+    pub fn new(time: Timestamp) -> Self {
+        Self(Arc::new(Mutex::new(time)))
+    }
+
+    /// Set the current time.
+    pub fn set(&self, time: Timestamp) {
+        *self.lock() = time;
+    }
+
+    /// Move the current time forward.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time would overflow jiff's supported range of timestamps.
+    pub fn advance(&self, duration: Duration) {
+        let mut time = self.lock();
+        *time = time.checked_add(duration).expect("ManualClock overflowed");
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Timestamp> {
+        // a Timestamp can't be left in an inconsistent state, so ignore poisoning
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        *self.lock()
+    }
+}
+
+/// A shared, type-erased [`Clock`], which implements `Debug` and `Clone` so that
+/// [`EventFormatter`](crate::EventFormatter) can derive them.
+#[derive(Clone)]
+pub(crate) struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub(crate) fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+}
+
+impl Clock for SharedClock {
+    fn now(&self) -> Timestamp {
+        self.0.now()
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Clock")
+    }
+}
@@ -4,24 +4,53 @@
 //! inlined at the top-level of the `serif` crate.
 
 use std::env::{self, VarError};
-use std::io::{self, IsTerminal};
+use std::fs::{File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use tracing_subscriber::filter::{Directive, EnvFilter, LevelFilter};
+use terminal_size::{Width, terminal_size_of};
+use tracing_core::{Dispatch, Subscriber, dispatcher};
+use tracing_log::{AsLog, LogTracer};
+use tracing_subscriber::Registry;
+use tracing_subscriber::filter::{Directive, EnvFilter, FilterExt, LevelFilter, filter_fn};
+use tracing_subscriber::layer::{Filter, Layer, SubscriberExt};
 
-use crate::{EventFormatter, FieldFormatter, TimeFormat};
+use crate::summary::{SUMMARY_TARGET, SummaryLayer, SummaryState};
+use crate::suppress::{FLUSH_TARGET, SuppressLayer, Suppressor};
+use crate::{
+    Buffered, Clock, EventFormatter, FieldFormatter, Format, Guard, NonBlocking, OverflowPolicy,
+    RingBuffer, TargetWidth, TimeFormat,
+};
+#[cfg(unix)]
+use crate::{Journald, Syslog};
 
 /// The destination for where serif will write logs.
 ///
-/// Only stdout and stderr are supported, due to type system limitations and how [`FmtSubscriber`]
-/// is generic over its Writer type.
-///
-/// [`FmtSubscriber`]: tracing_subscriber::fmt::Subscriber
-#[derive(Debug, Clone, Copy)]
+/// Logs can be written to stdout, stderr, or a file, or on Unix sent to a local syslog daemon or
+/// to systemd-journald. ANSI colors are never used for syslog and journald, regardless of the
+/// [`ColorMode`].
+#[derive(Debug, Clone)]
 pub enum Output {
     /// Log to standard output. This is the default.
     Stdout,
     /// Log to standard error.
     Stderr,
+    /// Log to a file. The file is created if it doesn't exist, and appended to if it does.
+    File(PathBuf),
+    /// Log to a local syslog daemon. See [`Syslog`] for details.
+    ///
+    /// Syslog messages are always sent directly, so [`Config::with_buffered`] and
+    /// [`Config::with_non_blocking`] have no effect.
+    #[cfg(unix)]
+    Syslog(Syslog),
+    /// Log to systemd-journald using its native protocol. See [`Journald`] for details.
+    ///
+    /// Journal entries are always sent directly, so [`Config::with_buffered`] and
+    /// [`Config::with_non_blocking`] have no effect.
+    #[cfg(unix)]
+    Journald(Journald),
 }
 
 impl Default for Output {
@@ -40,33 +69,102 @@ impl Output {
         match self {
             Output::Stdout => io::stdout().is_terminal(),
             Output::Stderr => io::stderr().is_terminal(),
+            Output::File(_) => false,
+            #[cfg(unix)]
+            Output::Syslog(_) | Output::Journald(_) => false,
+        }
+    }
+
+    /// Is this output stream connected to the journal?
+    fn is_journal(&self) -> bool {
+        match self {
+            #[cfg(unix)]
+            Output::Stdout => crate::journald::is_journal_stream(io::stdout()),
+            #[cfg(unix)]
+            Output::Stderr => crate::journald::is_journal_stream(io::stderr()),
+            _ => false,
         }
     }
+
+    /// Open this output as a boxed writer.
+    fn open(self) -> Box<dyn Write + Send> {
+        match self {
+            Output::Stdout => Box::new(io::stdout()),
+            Output::Stderr => Box::new(io::stderr()),
+            Output::File(_) => Box::new(self.open_file()),
+            #[cfg(unix)]
+            Output::Syslog(_) | Output::Journald(_) => {
+                unreachable!("{self:?} is never opened as a stream")
+            }
+        }
+    }
+
+    /// Open the log file for `Output::File`, creating it if necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be opened, or if this isn't `Output::File`.
+    fn open_file(&self) -> File {
+        let Output::File(path) = self else {
+            unreachable!("open_file called for {self:?}");
+        };
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|err| panic!("Unable to open log file '{}': {err}", path.display()))
+    }
+
+    /// Get the width of this output stream, if it's a terminal.
+    ///
+    /// The `COLUMNS` environment variable overrides the detected width, if it's set to a positive
+    /// number.
+    fn terminal_width(&self) -> Option<usize> {
+        if !self.is_terminal() {
+            return None;
+        }
+
+        let columns = env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).filter(|&c| c > 0);
+        if columns.is_some() {
+            return columns;
+        }
+
+        let size = match self {
+            Output::Stdout => terminal_size_of(io::stdout()),
+            Output::Stderr => terminal_size_of(io::stderr()),
+            Output::File(_) => None,
+            #[cfg(unix)]
+            Output::Syslog(_) | Output::Journald(_) => None,
+        };
+        size.map(|(Width(width), _)| width.into())
+    }
 }
 
 /// When to apply ANSI colors to output.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum ColorMode {
     /// Apply colors if the output (stdout or stderr) is a terminal. This is the default.
     ///
     /// Additionally, if the `NO_COLOR` environment variable is set to any non-empty string, ANSI
     /// coloring will be disabled.
+    #[default]
     Auto,
-    /// Always apply ANSI colors.
+    /// Always apply ANSI colors, except to syslog and journald.
     Always,
     /// Never apply ANSI colors.
     Never,
 }
 
-impl Default for ColorMode {
-    fn default() -> Self {
-        Self::Auto
-    }
-}
-
 impl ColorMode {
     /// Whether to enable ANSI colors for a given Output destination.
-    fn enable_for(&self, output: Output) -> bool {
+    fn enable_for(&self, output: &Output) -> bool {
+        // messages sent to a socket are stored and displayed by other tools, which don't expect
+        // escape sequences
+        #[cfg(unix)]
+        if matches!(output, Output::Syslog(_) | Output::Journald(_)) {
+            return false;
+        }
         match self {
             Self::Auto => {
                 if env::var_os("NO_COLOR").map(|s| !s.is_empty()).unwrap_or(false) {
@@ -81,13 +179,155 @@ impl ColorMode {
     }
 }
 
+/// An environment profile, which adjusts the event format for where the output is collected.
+///
+/// Log collectors like systemd-journald and container runtimes record their own timestamp for
+/// each line, so serif's timestamps are redundant there. Profiles only change the event format
+/// when writing formatted text, they have no effect on [`Output::Journald`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Use the configured event format without changes. This is the default.
+    #[default]
+    Standard,
+    /// A systemd service whose stdout or stderr is connected to the journal. Timestamps are
+    /// omitted, and each event starts with an sd-daemon level prefix so that journald records
+    /// its priority, see [`EventFormatter::with_level_prefix`].
+    Journal,
+    /// A container whose output is collected by the container runtime. Timestamps are omitted.
+    Container,
+    /// Choose a profile with [`Profile::detect`] when [`Config::init`] is called.
+    Auto,
+}
+
+impl Profile {
+    /// Detect the profile for an output.
+    ///
+    /// Only stdout and stderr are collected, so other outputs and terminals always use
+    /// [`Profile::Standard`]. Otherwise:
+    ///   * [`Profile::Journal`] is used if the output is connected to the journal, using the
+    ///     `JOURNAL_STREAM` environment variable like [`Journald::stderr_is_journal`].
+    ///   * [`Profile::Container`] is used if the `container` environment variable is set (by
+    ///     systemd-nspawn and others), if `KUBERNETES_SERVICE_HOST` is set, or if `/.dockerenv` or
+    ///     `/run/.containerenv` exist (created by Docker and Podman).
+    pub fn detect(output: &Output) -> Self {
+        if !matches!(output, Output::Stdout | Output::Stderr) || output.is_terminal() {
+            Self::Standard
+        } else if output.is_journal() {
+            Self::Journal
+        } else if in_container() {
+            Self::Container
+        } else {
+            Self::Standard
+        }
+    }
+
+    /// Resolve [`Profile::Auto`] for an output.
+    fn resolve(self, output: &Output) -> Self {
+        match self {
+            Self::Auto => Self::detect(output),
+            _ => self,
+        }
+    }
+
+    /// Adjust an event formatter for this profile.
+    fn apply(self, event_formatter: EventFormatter) -> EventFormatter {
+        match self {
+            Self::Standard | Self::Auto => event_formatter,
+            Self::Journal => {
+                event_formatter.with_timestamp(TimeFormat::none()).with_level_prefix(true)
+            }
+            Self::Container => event_formatter.with_timestamp(TimeFormat::none()),
+        }
+    }
+}
+
+/// Are we running in a container?
+fn in_container() -> bool {
+    env::var_os("container").is_some_and(|c| !c.is_empty())
+        || env::var_os("KUBERNETES_SERVICE_HOST").is_some()
+        || Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+}
+
+/// Map a numeric "verbosity" value to a level, see [`Config::with_verbosity`].
+fn verbosity_level(verbosity: i32) -> LevelFilter {
+    match verbosity.clamp(-3, 2) {
+        -3 => LevelFilter::OFF,
+        -2 => LevelFilter::ERROR,
+        -1 => LevelFilter::WARN,
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        2 => LevelFilter::TRACE,
+        _ => unreachable!(),
+    }
+}
+
+/// Can `target` be used in a `target=level` filter directive?
+pub(crate) fn is_valid_target(target: &str) -> bool {
+    !target.is_empty() && !target.contains(['=', ',', '[', ']', '{', '}'])
+}
+
+/// Register a subscriber as the global default.
+///
+/// This is what [`SubscriberBuilder::init`] does, except that it never installs a [`LogTracer`],
+/// since serif does that itself.
+///
+/// [`SubscriberBuilder::init`]: tracing_subscriber::fmt::SubscriberBuilder::init
+fn set_global_default(subscriber: impl Subscriber + Send + Sync + 'static) {
+    dispatcher::set_global_default(Dispatch::new(subscriber))
+        .expect("Unable to install global subscriber");
+}
+
+/// Extend the output's filter to always enable serif's own events which write the end-of-run
+/// summary and pending suppression summaries, since directives like `myapp=debug` don't enable
+/// serif's targets.
+fn output_filter<S: Subscriber>(filter: EnvFilter) -> impl Filter<S> + Send + Sync + 'static {
+    let internal = filter_fn(|meta| matches!(meta.target(), SUMMARY_TARGET | FLUSH_TARGET));
+    filter.or(internal.with_max_level_hint(LevelFilter::WARN))
+}
+
 /// Builder style configuration for the `serif` tracing-subscriber implementation.
+///
+/// # Configuration files
+///
+/// With the `serde` feature enabled, `Config` implements `Deserialize` and `Serialize` so that it
+/// can be loaded from a table in an application's configuration file. For example, in TOML:
+///
+/// ```toml
+/// [logging]
+/// level = "info"                # the default directive, see with_default
+/// targets = { hyper = "warn" }  # levels for specific targets, see with_target_level
+/// color = "auto"                # "auto", "always", or "never"
+/// timestamp = "utc"             # a TimeFormat, like "none", "local", or "utc:%H:%M:%S"
+/// output = "/var/log/app.log"   # "stdout", "stderr", or a file path
+/// format = "full"               # "full" or "compact", applied before timestamp
+/// ```
+///
+/// Every key is optional and defaults to the same value as [`Config::new`], and unknown keys are
+/// an error. Other options, like field formatting, can't be set from a file and aren't
+/// serialized, but can be changed with builder methods after deserializing.
 #[derive(Debug, Clone)]
 pub struct Config {
-    event_formatter: EventFormatter,
-    output: Output,
-    color: ColorMode,
-    default_directive: Directive,
+    pub(crate) format: Format,
+    pub(crate) event_formatter: EventFormatter,
+    pub(crate) field_formatter: FieldFormatter,
+    pub(crate) output: Output,
+    pub(crate) color: ColorMode,
+    wrap: bool,
+    log_bridge: bool,
+    panic_hook: bool,
+    pub(crate) default_directive: Directive,
+    pub(crate) target_levels: Vec<(String, LevelFilter)>,
+    env_vars: Vec<String>,
+    env_merge: bool,
+    non_blocking: Option<(usize, OverflowPolicy)>,
+    flush_interval: Option<Duration>,
+    auto_journald: bool,
+    profile: Profile,
+    dedup_window: Option<Duration>,
+    rate_limit: Option<(u32, Duration)>,
+    summary: Option<usize>,
+    ring_buffer: Option<(RingBuffer, LevelFilter)>,
 }
 
 impl Default for Config {
@@ -102,14 +342,30 @@ impl Config {
     /// Create a new `Config` with the default configuration.
     pub fn new() -> Self {
         Self {
+            format: Default::default(),
             event_formatter: Default::default(),
+            field_formatter: Default::default(),
             output: Default::default(),
             color: Default::default(),
+            wrap: false,
+            log_bridge: true,
+            panic_hook: false,
             default_directive: LevelFilter::INFO.into(),
+            target_levels: Vec::new(),
+            env_vars: vec![String::from("RUST_LOG")],
+            env_merge: false,
+            non_blocking: None,
+            flush_interval: None,
+            auto_journald: false,
+            profile: Profile::Standard,
+            dedup_window: None,
+            rate_limit: None,
+            summary: None,
+            ring_buffer: None,
         }
     }
 
-    /// Change the output destination to stdout or stderr. The default is stdout.
+    /// Change the output destination to stdout, stderr, or a file. The default is stdout.
     pub fn with_output(self, output: Output) -> Self {
         Self { output, ..self }
     }
@@ -119,6 +375,176 @@ impl Config {
         Self { color, ..self }
     }
 
+    /// Enable or disable wrapping long messages to the width of the terminal. The default is
+    /// `false`.
+    ///
+    /// When enabled and the output is a terminal, messages are soft-wrapped at word boundaries
+    /// with wrapped lines indented to line up with the start of the message. The terminal width is
+    /// detected once when [`init`](Config::init) is called, and can be overridden by setting the
+    /// `COLUMNS` environment variable. See [`EventFormatter::with_wrap_width`] for more details.
+    pub fn with_wrap(self, wrap: bool) -> Self {
+        Self { wrap, ..self }
+    }
+
+    /// Write logs from a background thread, so that logging doesn't block on slow output. The
+    /// default is to write synchronously.
+    ///
+    /// Formatted events are sent to a queue holding up to `capacity` events, and `policy` controls
+    /// what happens when the queue is full. See [`NonBlocking`] for details.
+    ///
+    /// Hold the [`Guard`] returned by [`init`](Config::init) until the end of `main`, otherwise
+    /// events which are still queued when the process exits are lost.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_non_blocking(self, capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(capacity > 0, "NonBlocking queue capacity must be non-zero");
+        Self { non_blocking: Some((capacity, policy)), ..self }
+    }
+
+    /// Buffer log output, flushing at least once every `flush_interval`. The default is to write
+    /// each event without buffering.
+    ///
+    /// WARN and ERROR events are flushed immediately, so that messages leading up to a crash
+    /// aren't lost. See [`Buffered`] for details.
+    ///
+    /// Hold the [`Guard`] returned by [`init`](Config::init) until the end of `main`, otherwise
+    /// buffered events are lost when the process exits. Non-blocking output is already written in
+    /// batches, so this has no effect when [`with_non_blocking`](Config::with_non_blocking) is
+    /// enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `flush_interval` is zero.
+    pub fn with_buffered(self, flush_interval: Duration) -> Self {
+        assert!(!flush_interval.is_zero(), "Buffered flush interval must be non-zero");
+        Self { flush_interval: Some(flush_interval), ..self }
+    }
+
+    /// Collapse repeated identical events, which have the same callsite and message, within
+    /// `window`. The default is to log every event.
+    ///
+    /// The first event is logged, then identical events are suppressed until `window` has passed
+    /// since it was logged, or until the callsite logs a different message. The number of
+    /// suppressed events is then summarized in a dimmed line like `(repeated 1532 times)`, which
+    /// is written before the next event that's logged, or when the [`Guard`] is dropped.
+    ///
+    /// Suppression has no effect with [`Output::Journald`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    pub fn with_dedup(self, window: Duration) -> Self {
+        assert!(!window.is_zero(), "Dedup window must be non-zero");
+        Self { dedup_window: Some(window), ..self }
+    }
+
+    /// Limit how often each callsite can log, using a token bucket which allows bursts of up to
+    /// `burst` events and refills at a rate of `burst` events per `interval`. The default is
+    /// unlimited.
+    ///
+    /// Events beyond the limit are dropped. Once the callsite can log again, the number of
+    /// dropped events is summarized in a dimmed line like `(12 events dropped by rate limit)`,
+    /// which is written before the next event that's logged, or when the [`Guard`] is dropped.
+    /// Events suppressed by [`with_dedup`](Config::with_dedup) don't count towards the limit.
+    ///
+    /// Suppression has no effect with [`Output::Journald`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` or `interval` is zero.
+    pub fn with_rate_limit(self, burst: u32, interval: Duration) -> Self {
+        assert!(burst > 0, "Rate limit burst must be non-zero");
+        assert!(!interval.is_zero(), "Rate limit interval must be non-zero");
+        Self { rate_limit: Some((burst, interval)), ..self }
+    }
+
+    /// Count the events logged at each level, and keep the last `keep_last` WARN and ERROR lines,
+    /// so that a summary like `3 warnings, 1 error` can be printed at the end of the run. The
+    /// default is to not count events.
+    ///
+    /// The summary is printed when the [`Guard`] returned by [`init`](Config::init) is dropped, or
+    /// earlier by calling [`serif::summary`](crate::summary), which also returns the [`Counts`] so
+    /// that the exit code can depend on them. The kept lines are printed after the summary
+    /// without colors. Use `0` to only count events. Events suppressed by
+    /// [`with_dedup`](Config::with_dedup) or [`with_rate_limit`](Config::with_rate_limit) are
+    /// still counted.
+    ///
+    /// [`Counts`]: crate::Counts
+    pub fn with_summary(self, keep_last: usize) -> Self {
+        Self { summary: Some(keep_last), ..self }
+    }
+
+    /// Record formatted events in a [`RingBuffer`], at a separate level from the output's filter.
+    /// The default is to not record events.
+    ///
+    /// `level` is usually more verbose than the output, so that debug logs can be dumped after a
+    /// failure even though the console only showed INFO. The output's filter, including
+    /// `RUST_LOG` and [`with_target_level`](Config::with_target_level), doesn't apply to the ring
+    /// buffer. Events are recorded without colors, wrapping, or [`with_profile`] changes. Events
+    /// which the output logs are still suppressed by [`with_dedup`] and [`with_rate_limit`].
+    ///
+    /// [`with_profile`]: Config::with_profile
+    /// [`with_dedup`]: Config::with_dedup
+    /// [`with_rate_limit`]: Config::with_rate_limit
+    pub fn with_ring_buffer(self, ring: RingBuffer, level: impl Into<LevelFilter>) -> Self {
+        Self { ring_buffer: Some((ring, level.into())), ..self }
+    }
+
+    /// Enable or disable automatically logging to journald when running as a systemd service. The
+    /// default is `false`.
+    ///
+    /// When enabled, [`init`](Config::init) checks whether stderr is connected to the journal
+    /// using [`Journald::stderr_is_journal`]. If so, and the journal's socket can be connected
+    /// to, the output is replaced with [`Output::Journald`] so that events are logged with
+    /// structured fields instead of as formatted text. Otherwise the configured output is used.
+    ///
+    /// This is only available on Unix platforms.
+    #[cfg(unix)]
+    pub fn with_auto_journald(self, auto_journald: bool) -> Self {
+        Self { auto_journald, ..self }
+    }
+
+    /// Set the environment profile, which adjusts the event format when logs are collected by
+    /// journald or a container runtime. The default is [`Profile::Standard`], which doesn't
+    /// change anything. Use [`Profile::Auto`] to detect the environment when
+    /// [`init`](Config::init) is called.
+    ///
+    /// The profile is applied after all other event formatting options, so it overrides
+    /// [`with_timestamp`](Config::with_timestamp).
+    pub fn with_profile(self, profile: Profile) -> Self {
+        Self { profile, ..self }
+    }
+
+    /// Enable or disable forwarding records from the [`log`] crate to tracing. The default is
+    /// `true`.
+    ///
+    /// When enabled, [`init`](Config::init) installs a [`LogTracer`] as the global logger, with
+    /// its max level set from the most verbose level enabled by the [`EnvFilter`]. Records from
+    /// the `log` crate are then filtered by their own targets (usually the module path of the
+    /// crate that logged them) and rendered the same way as tracing events.
+    ///
+    /// Disable this if the application installs its own `log` implementation. Otherwise `init`
+    /// panics before installing the global subscriber.
+    ///
+    /// [`log`]: https://lib.rs/crates/log
+    pub fn with_log_bridge(self, log_bridge: bool) -> Self {
+        Self { log_bridge, ..self }
+    }
+
+    /// Enable or disable logging panics through tracing. The default is `false`.
+    ///
+    /// When enabled, [`init`](Config::init) installs a panic hook which emits an ERROR event with
+    /// target `panic`, with the panic message as the message and fields for the `thread` name and
+    /// source `location`. If backtraces are enabled with the `RUST_BACKTRACE` or
+    /// `RUST_LIB_BACKTRACE` environment variables, the backtrace is included as a field too. The
+    /// previously installed panic hook (normally the default one that prints to stderr) is called
+    /// afterwards.
+    pub fn with_panic_hook(self, panic_hook: bool) -> Self {
+        Self { panic_hook, ..self }
+    }
+
     /// Set the default log directive. The default is the INFO level.
     ///
     /// You can call this with [`tracing::Level`] and [`tracing_subscriber::filter::LevelFilter`],
@@ -141,25 +567,125 @@ impl Config {
     ///   * `1`: debug
     ///   * `2` or greater: trace
     pub fn with_verbosity(self, verbosity: i32) -> Self {
-        let level = match verbosity.clamp(-3, 2) {
-            -3 => LevelFilter::OFF,
-            -2 => LevelFilter::ERROR,
-            -1 => LevelFilter::WARN,
-            0 => LevelFilter::INFO,
-            1 => LevelFilter::DEBUG,
-            2 => LevelFilter::TRACE,
-            _ => unreachable!(),
-        };
-        self.with_default(level)
+        self.with_default(verbosity_level(verbosity))
+    }
+
+    /// Set the log level for a target and its children, e.g. to make a noisy dependency quieter.
+    ///
+    /// Target levels are added to the default directive to make up the base directives, which are
+    /// used when the filter environment variable is unset or empty, or always if
+    /// [`with_env_merge`](Config::with_env_merge) is enabled. Setting the level of the same
+    /// target again replaces the previous level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` can't be used in a filter directive, e.g. if it contains `=` or `[`.
+    pub fn with_target_level(
+        mut self,
+        target: impl Into<String>,
+        level: impl Into<LevelFilter>,
+    ) -> Self {
+        let target = target.into();
+        if !is_valid_target(&target) {
+            panic!("Invalid target for a filter directive: '{target}'");
+        }
+        let level = level.into();
+        match self.target_levels.iter_mut().find(|(t, _)| *t == target) {
+            Some((_, old_level)) => *old_level = level,
+            None => self.target_levels.push((target, level)),
+        }
+        self
+    }
+
+    /// Set the log level for each of `targets` to WARN. See
+    /// [`with_target_level`](Config::with_target_level).
+    pub fn quiet_targets<I, S>(self, targets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        targets
+            .into_iter()
+            .fold(self, |config, target| config.with_target_level(target, LevelFilter::WARN))
+    }
+
+    /// Set the log level for a target using a numeric "verbosity" value, with the same mapping as
+    /// [`with_verbosity`](Config::with_verbosity). See
+    /// [`with_target_level`](Config::with_target_level).
+    pub fn with_verbosity_for(self, target: impl Into<String>, verbosity: i32) -> Self {
+        self.with_target_level(target, verbosity_level(verbosity))
+    }
+
+    /// Set the environment variable which is read for filter directives. The default is
+    /// `RUST_LOG`.
+    ///
+    /// This is a shortcut for [`with_env_vars`](Config::with_env_vars) with a single variable.
+    pub fn with_env_var(self, name: impl Into<String>) -> Self {
+        self.with_env_vars([name])
+    }
+
+    /// Set a list of environment variables which are read for filter directives, in priority
+    /// order. The default is `["RUST_LOG"]`.
+    ///
+    /// The first variable in the list which is set to a non-empty value is used, and the rest are
+    /// ignored. If none of them are set (or they're all empty), the default directive is used.
+    /// For example, `with_env_vars(["MYTOOL_LOG", "RUST_LOG"])` lets users configure logging
+    /// for just this application, but falls back to the usual `RUST_LOG`.
+    ///
+    /// An empty list disables reading filter directives from the environment.
+    pub fn with_env_vars<I, S>(self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { env_vars: names.into_iter().map(Into::into).collect(), ..self }
+    }
+
+    /// Enable or disable merging environment filter directives with the default directive. The
+    /// default is `false`.
+    ///
+    /// By default, a non-empty `RUST_LOG` (or other variable set by
+    /// [`with_env_vars`](Config::with_env_vars)) replaces the default directive entirely, so
+    /// `RUST_LOG=hyper=trace` enables TRACE logs for `hyper` and disables everything else. When
+    /// merging is enabled, the default directive set by [`with_default`](Config::with_default) or
+    /// [`with_verbosity`](Config::with_verbosity) and any target levels set by
+    /// [`with_target_level`](Config::with_target_level) are kept as the base, and directives from
+    /// the environment are layered on top, so the same `RUST_LOG` enables TRACE logs for `hyper`
+    /// and keeps INFO logs for everything else.
+    ///
+    /// When merging, the precedence of directives is:
+    ///   1. A directive from the environment replaces a base directive with the same target (and
+    ///      span and field filters). For example `RUST_LOG=warn` replaces a default of `info`.
+    ///   2. Otherwise, every directive is kept, and each event is filtered by the most specific
+    ///      directive which matches it, as usual for [`EnvFilter`].
+    ///
+    /// Use [`directives`](Config::directives) to inspect the resulting list of directives.
+    pub fn with_env_merge(self, env_merge: bool) -> Self {
+        Self { env_merge, ..self }
     }
 
     // EventFormatter builder methods
 
+    /// Select a preset output format. The default is [`Format::Full`].
+    ///
+    /// The preset sets the level labels, target abbreviation, and timestamp format, see
+    /// [`EventFormatter::with_format`]. Other event formatting options are unchanged, but a
+    /// timestamp set by an earlier call to [`with_timestamp`](Config::with_timestamp) is replaced.
+    pub fn with_format(self, format: Format) -> Self {
+        Self { format, event_formatter: self.event_formatter.with_format(format), ..self }
+    }
+
     /// Set the timestamp format for this Config.
     pub fn with_timestamp(self, time_format: TimeFormat) -> Self {
         Self { event_formatter: self.event_formatter.with_timestamp(time_format), ..self }
     }
 
+    /// Set the [`Clock`] used to get the current time for timestamps. The default is
+    /// [`SystemClock`](crate::SystemClock).
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self { event_formatter: self.event_formatter.with_clock(clock), ..self }
+    }
+
     /// Set whether or not an event's target is displayed.
     pub fn with_target(self, display_target: bool) -> Self {
         Self { event_formatter: self.event_formatter.with_target(display_target), ..self }
@@ -170,62 +696,272 @@ impl Config {
         Self { event_formatter: self.event_formatter.with_scope(display_scope), ..self }
     }
 
+    /// Set the width of the span scope and target column, to align messages.
+    pub fn with_target_width(self, target_width: TargetWidth) -> Self {
+        Self { event_formatter: self.event_formatter.with_target_width(target_width), ..self }
+    }
+
+    // FieldFormatter builder methods
+
+    /// Set the field formatter for this Config, e.g. to hide some fields from the output.
+    pub fn with_field_formatter(self, field_formatter: FieldFormatter) -> Self {
+        Self { field_formatter, ..self }
+    }
+
     /// Finalize this Config and register it as the global default tracing subscriber.
     ///
+    /// Returns a [`Guard`] which flushes log output when it's dropped, so hold on to it until the
+    /// end of `main`, like `let _guard = config.init();`.
+    ///
     /// # Panics
     ///
-    /// Panics if the `RUST_LOG` environment variable is invalid (see [`make_env_filter`]) or if
-    /// another global subscriber is already installed (see [`SubscriberBuilder::init`]).
+    /// Panics if the filter environment variable is invalid (see [`make_env_filter`]), if the
+    /// output is a file which can't be opened or a syslog or journald socket which can't be
+    /// connected to, if another global subscriber is already installed, or if the `log` bridge is
+    /// enabled and another global logger is already installed (see [`with_log_bridge`]).
     ///
     /// [`make_env_filter`]: Config::make_env_filter
-    /// [`SubscriberBuilder::init`]: tracing_subscriber::fmt::SubscriberBuilder::init
-    pub fn init(self) {
-        // FmtSubscriber (and SubscriberBuilder) are generic over the MakeWriter type given to
-        // with_writer, so split up the logic to avoid having to wrap stdout/stderr in an extra
-        // Box. Due to unnecessary implementation restrictions, with_ansi must be set before
-        // setting the custom event formatter. See https://github.com/tokio-rs/tracing/issues/1867
-        let builder = tracing_subscriber::fmt()
-            .with_env_filter(self.make_env_filter())
-            .with_ansi(self.color.enable_for(self.output))
+    /// [`with_log_bridge`]: Config::with_log_bridge
+    #[must_use = "dropping the guard flushes output immediately, hold it until the end of `main`"]
+    pub fn init(self) -> Guard {
+        let filter = self.make_env_filter();
+        let mut max_level = filter.max_level_hint().unwrap_or(LevelFilter::TRACE);
+        if let Some((_, ring_level)) = &self.ring_buffer {
+            max_level = max_level.max(*ring_level);
+        }
+
+        #[cfg(unix)]
+        let journald = match &self.output {
+            Output::Journald(journald) => Some(journald.connect().unwrap_or_else(|err| {
+                panic!("Unable to connect to journald at '{}': {err}", journald.path().display())
+            })),
+            _ if self.auto_journald && Journald::stderr_is_journal() => {
+                Journald::new().connect().ok()
+            }
+            _ => None,
+        };
+        #[cfg(not(unix))]
+        let journald = None::<std::convert::Infallible>;
+
+        // Install the log bridge first, so that if it fails then nothing global has been installed
+        // yet and there's no output which a Guard would need to flush.
+        if self.log_bridge {
+            LogTracer::builder()
+                .with_max_level(max_level.as_log())
+                .init()
+                .expect("Unable to install the log bridge, another logger is already installed");
+        }
+
+        let summary = self.summary.map(SummaryState::install);
+        let counter = summary.clone().map(SummaryLayer);
+        let mut guard = match journald {
+            #[cfg(unix)]
+            Some(layer) => {
+                let registry =
+                    tracing_subscriber::registry().with(self.ring_layer(summary.as_ref()));
+                let layer = layer.and_then(counter).with_filter(output_filter(filter));
+                set_global_default(registry.with(layer));
+                Guard::default()
+            }
+            None => self.init_fmt(filter, summary, counter),
+        };
+        if self.summary.is_some() {
+            guard = guard.with_summary();
+        }
+
+        if self.panic_hook {
+            crate::panic::install_hook();
+        }
+
+        guard
+    }
+
+    /// Register a subscriber which formats events for all outputs besides journald, returning
+    /// the output's Guard.
+    fn init_fmt(
+        &self,
+        filter: EnvFilter,
+        summary: Option<Arc<SummaryState>>,
+        counter: Option<SummaryLayer>,
+    ) -> Guard {
+        // The fmt Layer is generic over the MakeWriter type given to with_writer, so split up the
+        // logic to avoid having to wrap stdout/stderr in an extra Box. Due to unnecessary
+        // implementation restrictions, with_ansi must be set before setting the custom event
+        // formatter. See https://github.com/tokio-rs/tracing/issues/1867
+        let wrap_width = if self.wrap { self.output.terminal_width() } else { None };
+        let mut event_formatter = self.output_event_formatter();
+        let mut suppressor = None;
+        if self.dedup_window.is_some() || self.rate_limit.is_some() {
+            suppressor = Some(Suppressor::new(self.dedup_window, self.rate_limit));
+        }
+        if let Some(suppressor) = &suppressor {
+            event_formatter = event_formatter.with_suppressor(suppressor.clone());
+        }
+        let suppress = suppressor.clone().map(SuppressLayer);
+        if let Some(summary) = &summary {
+            event_formatter = event_formatter.with_summary(summary.clone(), true);
+        }
+        let console = tracing_subscriber::fmt::layer()
+            .with_ansi(self.color.enable_for(&self.output))
             // register custom formatter types
-            .event_format(self.event_formatter)
-            .fmt_fields(FieldFormatter::new());
+            .event_format(event_formatter.with_wrap_width(wrap_width))
+            .fmt_fields(self.field_formatter.clone());
+        let registry = tracing_subscriber::registry().with(self.ring_layer(summary.as_ref()));
 
-        match self.output {
-            Output::Stdout => builder.with_writer(io::stdout).init(),
-            Output::Stderr => builder.with_writer(io::stderr).init(),
+        let filter = output_filter(filter);
+        let guard = match (&self.output, self.non_blocking, self.flush_interval) {
+            #[cfg(unix)]
+            (Output::Syslog(syslog), _, _) => {
+                let writer = syslog.connect().unwrap_or_else(|err| {
+                    panic!("Unable to connect to syslog at '{}': {err}", syslog.path().display())
+                });
+                let console = console.with_writer(writer).and_then(suppress).and_then(counter);
+                set_global_default(registry.with(console.with_filter(filter)));
+                Guard::default()
+            }
+            #[cfg(unix)]
+            (Output::Journald(_), _, _) => unreachable!("journald doesn't use FmtSubscriber"),
+            (output, Some((capacity, policy)), _) => {
+                let (writer, guard) = NonBlocking::new(output.clone().open(), capacity, policy);
+                let console = console.with_writer(writer).and_then(suppress).and_then(counter);
+                set_global_default(registry.with(console.with_filter(filter)));
+                guard
+            }
+            (output, None, Some(flush_interval)) => {
+                let (writer, guard) = Buffered::new(output.clone().open(), flush_interval);
+                let console = console.with_writer(writer).and_then(suppress).and_then(counter);
+                set_global_default(registry.with(console.with_filter(filter)));
+                guard
+            }
+            (Output::Stdout, None, None) => {
+                let console = console.with_writer(io::stdout).and_then(suppress).and_then(counter);
+                set_global_default(registry.with(console.with_filter(filter)));
+                Guard::default()
+            }
+            (Output::Stderr, None, None) => {
+                let console = console.with_writer(io::stderr).and_then(suppress).and_then(counter);
+                set_global_default(registry.with(console.with_filter(filter)));
+                Guard::default()
+            }
+            (output @ Output::File(_), None, None) => {
+                let writer = Arc::new(output.open_file());
+                let console = console.with_writer(writer).and_then(suppress).and_then(counter);
+                set_global_default(registry.with(console.with_filter(filter)));
+                Guard::default()
+            }
+        };
+        match suppressor {
+            Some(suppressor) => guard.with_suppressor(suppressor),
+            None => guard,
         }
     }
 
+    /// The event formatter for the output, adjusted by the profile.
+    pub(crate) fn output_event_formatter(&self) -> EventFormatter {
+        self.profile.resolve(&self.output).apply(self.event_formatter.clone())
+    }
+
+    /// Create the layer which records events in the ring buffer, if there is one. The event
+    /// format is unchanged by the profile, so that the buffer keeps timestamps. The summary's
+    /// kept lines are recorded after the summary event like in the output.
+    fn ring_layer(
+        &self,
+        summary: Option<&Arc<SummaryState>>,
+    ) -> impl Layer<Registry> + Send + Sync + 'static {
+        self.ring_buffer.as_ref().map(|(ring, level)| {
+            let mut event_formatter = self.event_formatter.clone().with_own_seen_width();
+            if let Some(summary) = summary {
+                event_formatter = event_formatter.with_summary(summary.clone(), false);
+            }
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .event_format(event_formatter)
+                .fmt_fields(self.field_formatter.clone())
+                .with_writer(ring.clone())
+                .with_filter(*level)
+        })
+    }
+
+    /// The selected output format preset, see [`with_format`](Config::with_format).
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// The selected output, see [`with_output`](Config::with_output).
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// The selected color mode, see [`with_color`](Config::with_color).
+    pub fn color(&self) -> ColorMode {
+        self.color
+    }
+
     /// Create an [`EnvFilter`] from this Config.
     ///
     /// # Panics
     ///
-    /// Panics if the `RUST_LOG` environment variable contains invalid unicode, or if it contains
+    /// Panics if the selected environment variable (`RUST_LOG` by default, see
+    /// [`with_env_vars`](Config::with_env_vars)) contains invalid unicode, or if it contains
     /// invalid [`EnvFilter`] directives.
     pub fn make_env_filter(&self) -> EnvFilter {
+        self.directives().into_iter().fold(EnvFilter::default(), EnvFilter::add_directive)
+    }
+
+    /// Get the list of filter directives which [`make_env_filter`](Config::make_env_filter) and
+    /// [`init`](Config::init) will use, based on the current environment.
+    ///
+    /// If no filter environment variable is set, this is the base directives: the default
+    /// directive followed by any target levels set with
+    /// [`with_target_level`](Config::with_target_level). Otherwise it's the directives from the
+    /// environment, preceded by the base directives if merging is enabled with
+    /// [`with_env_merge`](Config::with_env_merge).
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`make_env_filter`](Config::make_env_filter).
+    pub fn directives(&self) -> Vec<Directive> {
         // EnvFilter's handling of defaults and fallbacks is wonky and confusing (there's a number
         // of github issues so hopefully it's improved eventually). So we sidestep all that mess
-        // and handle the logic ourselves. If RUST_LOG is unset or empty, then use our fallback. If
-        // RUST_LOG is set, use it with no default/fallback, and use the try_new method to cause
-        // errors on any invalid directives.
-        let env_str = match env::var("RUST_LOG") {
+        // and handle the logic ourselves. If the env vars are all unset or empty, then use our
+        // fallback. If one is set, use it with no default/fallback (unless merging), and panic on
+        // any invalid directives.
+        let targets = self.target_levels.iter().map(|(target, level)| {
+            format!("{target}={level}")
+                .parse()
+                .expect("target level directives should always be valid")
+        });
+        let base = std::iter::once(self.default_directive.clone()).chain(targets);
+        match self.env_filter_str() {
+            Some((name, filter_str)) => {
+                debug_assert!(!filter_str.is_empty());
+                let env_directives = filter_str
+                    .split(',')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse::<Directive>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap_or_else(|err| {
+                        panic!("Invalid {name} filter string '{filter_str}': {err}")
+                    });
+                if self.env_merge {
+                    base.into_iter().chain(env_directives).collect()
+                } else {
+                    env_directives
+                }
+            }
+            None => base.collect(),
+        }
+    }
+
+    /// Get the name and value of the first non-empty filter environment variable.
+    fn env_filter_str(&self) -> Option<(&str, String)> {
+        self.env_vars.iter().find_map(|name| match env::var(name) {
             Ok(val) if val.is_empty() => None,
-            Ok(val) => Some(val),
+            Ok(val) => Some((name.as_str(), val)),
             Err(VarError::NotPresent) => None,
             Err(VarError::NotUnicode(val)) => {
-                panic!("The RUST_LOG environment variable isn't valid unicode: {val:?}")
-            }
-        };
-
-        match &env_str {
-            Some(filter_str) => {
-                debug_assert!(!filter_str.is_empty());
-                EnvFilter::try_new(filter_str).unwrap_or_else(|err| {
-                    panic!("Invalid RUST_LOG filter string '{filter_str}': {err}")
-                })
+                panic!("The {name} environment variable isn't valid unicode: {val:?}")
             }
-            None => EnvFilter::default().add_directive(self.default_directive.clone()),
-        }
+        })
     }
 }
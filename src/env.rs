@@ -0,0 +1,243 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Parsing serif's configuration types from strings and environment variables. This module is
+//! private, but its pub types are exported and inlined at the top-level of the `serif` crate.
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{ColorMode, Config, Format, Output, TimeFormat};
+
+/// An error parsing one of serif's configuration types from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    what: &'static str,
+    value: String,
+    expected: String,
+}
+
+impl ParseError {
+    fn new(what: &'static str, value: &str, expected: impl Into<String>) -> Self {
+        Self { what, value: value.into(), expected: expected.into() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {} '{}', expected {}", self.what, self.value, self.expected)
+    }
+}
+
+impl Error for ParseError {}
+
+/// An error reading serif's configuration from environment variables, returned by
+/// [`Config::from_env`] and [`Config::apply_env`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvError {
+    var: &'static str,
+    source: ParseError,
+}
+
+impl EnvError {
+    /// The name of the environment variable which has an invalid value.
+    pub fn var(&self) -> &str {
+        self.var
+    }
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.var, self.source)
+    }
+}
+
+impl Error for EnvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = ParseError;
+
+    /// Parse `auto`, `always`, or `never`, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(ParseError::new("color mode", s, "'auto', 'always', or 'never'")),
+        }
+    }
+}
+
+impl FromStr for Output {
+    type Err = ParseError;
+
+    /// Parse `stdout`, `stderr`, `syslog`, or `journald`, ignoring ASCII case. The last two use
+    /// the default [`Syslog`](crate::Syslog) or [`Journald`](crate::Journald) options, and are
+    /// only available on Unix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "stdout" => Ok(Self::Stdout),
+            "stderr" => Ok(Self::Stderr),
+            #[cfg(unix)]
+            "syslog" => Ok(Self::Syslog(crate::Syslog::new())),
+            #[cfg(unix)]
+            "journald" => Ok(Self::Journald(crate::Journald::new())),
+            #[cfg(unix)]
+            _ => Err(ParseError::new("output", s, "'stdout', 'stderr', 'syslog', or 'journald'")),
+            #[cfg(not(unix))]
+            _ => Err(ParseError::new("output", s, "'stdout' or 'stderr'")),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseError;
+
+    /// Parse `full` or `compact`, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "compact" => Ok(Self::Compact),
+            _ => Err(ParseError::new("format", s, "'full' or 'compact'")),
+        }
+    }
+}
+
+impl FromStr for TimeFormat {
+    type Err = ParseError;
+
+    /// Parse a timestamp format.
+    ///
+    /// Accepted values are `none` (or `off`), `local`, `utc`, and `local:<format>` or
+    /// `utc:<format>` for a custom [`strtime`](jiff::fmt::strtime) format. Any other value
+    /// containing a `%` is used as a custom format in the local timezone.
+    ///
+    /// Unlike [`TimeFormat::local_custom`] and [`TimeFormat::utc_custom`], an invalid custom format
+    /// returns an error rather than panicking.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const EXPECTED: &str = "'none', 'local', 'utc', 'local:<format>', or 'utc:<format>'";
+
+        let format = match s.split_once(':') {
+            Some((tz, format)) if tz.eq_ignore_ascii_case("local") => {
+                TimeFormat::local_custom_unchecked(format)
+            }
+            Some((tz, format)) if tz.eq_ignore_ascii_case("utc") => {
+                TimeFormat::utc_custom_unchecked(format)
+            }
+            _ => match s.to_ascii_lowercase().as_str() {
+                "none" | "off" => return Ok(TimeFormat::none()),
+                "local" => return Ok(TimeFormat::local()),
+                "utc" => return Ok(TimeFormat::utc()),
+                _ if s.contains('%') => TimeFormat::local_custom_unchecked(s),
+                _ => return Err(ParseError::new("time format", s, EXPECTED)),
+            },
+        };
+
+        match format.validate() {
+            Ok(()) => Ok(format),
+            Err(err) => Err(ParseError::new("time format", s, format!("a valid format ({err})"))),
+        }
+    }
+}
+
+/// Parse a boolean flag like `1`, `true`, `yes`, `on`, or `0`, `false`, `no`, `off`.
+fn parse_bool(s: &str) -> Result<bool, ParseError> {
+    match s.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(ParseError::new("boolean", s, "'true' or 'false'")),
+    }
+}
+
+/// Read an environment variable, treating unset and empty the same.
+fn var(name: &'static str) -> Result<Option<String>, EnvError> {
+    match env::var(name) {
+        Ok(val) if val.is_empty() => Ok(None),
+        Ok(val) => Ok(Some(val)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(val)) => Err(EnvError {
+            var: name,
+            source: ParseError::new("value", &val.to_string_lossy(), "valid unicode"),
+        }),
+    }
+}
+
+/// Read and parse an environment variable, treating unset and empty the same.
+fn parse_var<T>(
+    name: &'static str,
+    parse: impl FnOnce(&str) -> Result<T, ParseError>,
+) -> Result<Option<T>, EnvError> {
+    match var(name)? {
+        Some(val) => parse(&val).map(Some).map_err(|source| EnvError { var: name, source }),
+        None => Ok(None),
+    }
+}
+
+/// Get the color mode from `SERIF_COLOR`, or from the `CLICOLOR_FORCE` and `CLICOLOR` conventions.
+fn color_from_env() -> Result<Option<ColorMode>, EnvError> {
+    if let Some(color) = parse_var("SERIF_COLOR", str::parse)? {
+        return Ok(Some(color));
+    }
+    if var("CLICOLOR_FORCE")?.is_some_and(|val| val != "0") {
+        return Ok(Some(ColorMode::Always));
+    }
+    if var("CLICOLOR")?.is_some_and(|val| val == "0") {
+        return Ok(Some(ColorMode::Never));
+    }
+    Ok(None)
+}
+
+impl Config {
+    /// Create a new `Config` with the default configuration, then apply overrides from
+    /// environment variables. See [`apply_env`](Config::apply_env) for details.
+    pub fn from_env() -> Result<Self, EnvError> {
+        Self::new().apply_env()
+    }
+
+    /// Override this Config's presentation options with environment variables.
+    ///
+    /// This lets end users of an application change how logs look without command line flags.
+    /// Variables which are unset or empty are ignored. The variables are:
+    ///   * `SERIF_FORMAT`: `full` or `compact`, see [`with_format`](Config::with_format). This is
+    ///     applied first, so that `SERIF_TIME` overrides the preset's timestamp format.
+    ///   * `SERIF_TIME`: a [`TimeFormat`] like `none`, `local`, `utc`, or `utc:%H:%M:%S`, see
+    ///     [`TimeFormat`'s `FromStr` implementation](TimeFormat#impl-FromStr-for-TimeFormat).
+    ///   * `SERIF_TARGET` and `SERIF_SCOPE`: `true` or `false` (or `1`/`0`, `yes`/`no`,
+    ///     `on`/`off`) to show or hide event targets and span scopes.
+    ///   * `SERIF_OUTPUT`: `stdout`, `stderr`, `syslog`, or `journald`.
+    ///   * `SERIF_COLOR`: `auto`, `always`, or `never`. If it's not set, then a `CLICOLOR_FORCE`
+    ///     value other than `0` means `always`, and `CLICOLOR=0` means `never`.
+    ///
+    /// The log filter is still read from `RUST_LOG` when [`init`](Config::init) is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first variable found with an invalid value.
+    pub fn apply_env(self) -> Result<Self, EnvError> {
+        let mut config = self;
+        if let Some(format) = parse_var("SERIF_FORMAT", str::parse)? {
+            config = config.with_format(format);
+        }
+        if let Some(time_format) = parse_var("SERIF_TIME", str::parse)? {
+            config = config.with_timestamp(time_format);
+        }
+        if let Some(display_target) = parse_var("SERIF_TARGET", parse_bool)? {
+            config = config.with_target(display_target);
+        }
+        if let Some(display_scope) = parse_var("SERIF_SCOPE", parse_bool)? {
+            config = config.with_scope(display_scope);
+        }
+        if let Some(output) = parse_var("SERIF_OUTPUT", str::parse)? {
+            config = config.with_output(output);
+        }
+        if let Some(color) = color_from_env()? {
+            config = config.with_color(color);
+        }
+        Ok(config)
+    }
+}
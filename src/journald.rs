@@ -0,0 +1,256 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Output to systemd-journald using its native protocol. This module is private, but its pub
+//! types are exported and inlined at the top-level of the `serif` crate.
+
+use std::env;
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::os::fd::AsFd;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::severity;
+use crate::syslog::default_app_name;
+
+/// Fields which serif sets for every event. Event fields with these names are prefixed with `F_`.
+const RESERVED_FIELDS: &[&str] =
+    &["MESSAGE", "PRIORITY", "CODE_FILE", "CODE_LINE", "TARGET", "SPANS", "SYSLOG_IDENTIFIER"];
+
+/// Options for logging to systemd-journald with [`Output::Journald`](crate::Output::Journald).
+///
+/// Events are sent to the journal's socket using its [native protocol], with structured fields
+/// rather than formatted text, so [`EventFormatter`](crate::EventFormatter) and
+/// [`FieldFormatter`](crate::FieldFormatter) options aren't used. Each journal entry has these
+/// fields:
+///   * `MESSAGE`: the event's message.
+///   * `PRIORITY`: the syslog severity of the event's level, see [`Syslog`](crate::Syslog).
+///   * `CODE_FILE` and `CODE_LINE`: the source location of the event, if known.
+///   * `TARGET`: the event's target.
+///   * `SPANS`: the names of the event's span scope, separated by `:`, if it's in a span.
+///   * `SYSLOG_IDENTIFIER`: the app name, which defaults to the current executable's file name.
+///   * Every other event field, with its name converted to uppercase and other characters
+///     replaced with `_`. Names which conflict with the fields above are prefixed with `F_`.
+///
+/// Entries which are too large to send in a single datagram are dropped.
+///
+/// [native protocol]: https://systemd.io/JOURNAL_NATIVE_PROTOCOL/
+#[derive(Debug, Clone)]
+pub struct Journald {
+    path: PathBuf,
+    app_name: Option<String>,
+}
+
+impl Default for Journald {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Journald {
+    /// The default path of the journal's native protocol socket.
+    pub const DEFAULT_PATH: &'static str = "/run/systemd/journal/socket";
+
+    /// Create the default journald options, using [`DEFAULT_PATH`](Journald::DEFAULT_PATH).
+    pub fn new() -> Self {
+        Self { path: PathBuf::from(Self::DEFAULT_PATH), app_name: None }
+    }
+
+    /// Set the path of the journal's socket.
+    pub fn with_path(self, path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), ..self }
+    }
+
+    /// Set the `SYSLOG_IDENTIFIER` sent with each entry. The default is the file name of the
+    /// current executable.
+    pub fn with_app_name(self, app_name: impl Into<String>) -> Self {
+        Self { app_name: Some(app_name.into()), ..self }
+    }
+
+    /// The path of the journal's socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Is this the default configuration?
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn is_default(&self) -> bool {
+        self.path == Path::new(Self::DEFAULT_PATH) && self.app_name.is_none()
+    }
+
+    /// Is stderr connected to the journal?
+    ///
+    /// systemd sets the `JOURNAL_STREAM` environment variable to the device and inode numbers of
+    /// the stdout/stderr stream of services which log to the journal. If those match stderr, then
+    /// its output is going to the journal. A terminal is never considered to be the journal.
+    pub fn stderr_is_journal() -> bool {
+        is_journal_stream(io::stderr())
+    }
+
+    /// Connect to the journal's socket, returning a [`Layer`] which can be used directly with a
+    /// tracing-subscriber [`Registry`](tracing_subscriber::Registry).
+    pub fn connect(&self) -> io::Result<JournaldLayer> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&self.path)?;
+        let app_name = self.app_name.clone().unwrap_or_else(default_app_name);
+        Ok(JournaldLayer { socket, path: self.path.clone(), app_name })
+    }
+}
+
+/// Is this stream connected to the journal? See [`Journald::stderr_is_journal`].
+pub(crate) fn is_journal_stream(stream: impl AsFd + IsTerminal) -> bool {
+    let Some(journal_stream) = env::var_os("JOURNAL_STREAM") else {
+        return false;
+    };
+    let Some((dev, ino)) = journal_stream.to_str().and_then(|s| s.split_once(':')) else {
+        return false;
+    };
+    let (Ok(dev), Ok(ino)) = (dev.parse::<u64>(), ino.parse::<u64>()) else {
+        return false;
+    };
+
+    if stream.is_terminal() {
+        return false;
+    }
+    let Ok(fd) = stream.as_fd().try_clone_to_owned() else {
+        return false;
+    };
+    File::from(fd).metadata().is_ok_and(|meta| meta.dev() == dev && meta.ino() == ino)
+}
+
+/// A [`Layer`] which sends events to systemd-journald, created by [`Journald::connect`].
+pub struct JournaldLayer {
+    socket: UnixDatagram,
+    path: PathBuf,
+    app_name: String,
+}
+
+impl fmt::Debug for JournaldLayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JournaldLayer")
+            .field("path", &self.path)
+            .field("app_name", &self.app_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JournaldLayer {
+    fn send(&self, payload: &[u8]) -> io::Result<()> {
+        match self.socket.send(payload) {
+            Ok(_) => Ok(()),
+            Err(_) => {
+                // journald may have restarted, so reconnect and try once more.
+                self.socket.connect(&self.path)?;
+                self.socket.send(payload).map(|_| ())
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for JournaldLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let normalized = event.normalized_metadata();
+        let meta = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut visitor = JournaldVisitor { payload: Vec::new(), message: String::new() };
+        event.record(&mut visitor);
+        let JournaldVisitor { mut payload, message } = visitor;
+
+        put_field(&mut payload, "MESSAGE", message.as_bytes());
+        put_field(&mut payload, "PRIORITY", severity(meta.level()).to_string().as_bytes());
+        if let Some(file) = meta.file() {
+            put_field(&mut payload, "CODE_FILE", file.as_bytes());
+        }
+        if let Some(line) = meta.line() {
+            put_field(&mut payload, "CODE_LINE", line.to_string().as_bytes());
+        }
+        put_field(&mut payload, "TARGET", meta.target().as_bytes());
+        if let Some(scope) = ctx.event_scope(event) {
+            let mut spans = String::new();
+            for span in scope.from_root() {
+                if !spans.is_empty() {
+                    spans.push(':');
+                }
+                spans.push_str(span.name());
+            }
+            put_field(&mut payload, "SPANS", spans.as_bytes());
+        }
+        put_field(&mut payload, "SYSLOG_IDENTIFIER", self.app_name.as_bytes());
+
+        // Errors are ignored, the same as tracing-subscriber does when writing formatted output.
+        let _ = self.send(&payload);
+    }
+}
+
+/// Append a field to a native protocol payload.
+///
+/// Values without newlines are sent as `NAME=value\n`. Otherwise they're sent as `NAME\n`, the
+/// value's length as a little-endian u64, then the value and `\n`.
+fn put_field(payload: &mut Vec<u8>, name: &str, value: &[u8]) {
+    payload.extend_from_slice(name.as_bytes());
+    if value.contains(&b'\n') {
+        payload.push(b'\n');
+        payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        payload.push(b'=');
+    }
+    payload.extend_from_slice(value);
+    payload.push(b'\n');
+}
+
+/// Convert a tracing field name to a valid journal field name.
+///
+/// Journal field names may only contain uppercase ASCII letters, digits, and underscores, must
+/// start with a letter, and can be at most 64 characters.
+fn field_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if !out.starts_with(|c: char| c.is_ascii_alphabetic()) || RESERVED_FIELDS.contains(&&*out) {
+        out.insert_str(0, "F_");
+    }
+    out.truncate(64);
+    out
+}
+
+struct JournaldVisitor {
+    payload: Vec<u8>,
+    message: String,
+}
+
+impl JournaldVisitor {
+    fn record_value(&mut self, field: &Field, value: fmt::Arguments<'_>) {
+        if field.name() == "message" {
+            let _ = self.message.write_fmt(value);
+        } else if !field.name().starts_with("log.") {
+            // log.* fields are added by tracing-log and are already in the normalized metadata
+            put_field(&mut self.payload, &field_name(field.name()), value.to_string().as_bytes());
+        }
+    }
+}
+
+impl Visit for JournaldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_value(field, format_args!("{value:?}"));
+    }
+}
@@ -26,9 +26,9 @@
 //!
 //! # fn do_stuff() {}
 //! fn main() {
-//!     serif::Config::new()            // create config builder
-//!         .with_default(Level::DEBUG) // the default otherwise is INFO
-//!         .init();                    // finalize and register with tracing
+//!     let _guard = serif::Config::new() // create config builder
+//!         .with_default(Level::DEBUG)   // the default otherwise is INFO
+//!         .init();                      // finalize and register with tracing
 //!     info!("Hello World!");
 //!     do_stuff();
 //!     debug!("Finished doing stuff");
@@ -42,8 +42,9 @@
 //! ## ANSI Terminal Colors
 //!
 //! By default, Serif enables ANSI coloring when the output file descriptor (stdout or stderr) is
-//! a TTY and the environment variable `NO_COLOR` is either unset or empty. At the moment, the
-//! specific color styles are not customizable.
+//! a TTY and the environment variable `NO_COLOR` is either unset or empty. The styles of field
+//! names and values can be customized with [`FieldFormatter`], using the [`Color`] and [`Style`]
+//! types re-exported from [`nu_ansi_term`].
 //!
 //! A note to advanced users configuring a [`SubscriberBuilder`] manually: `EventFormatter` and
 //! `FieldFormatter` do not track whether ANSI colors are enabled directly, instead they obtain
@@ -60,17 +61,23 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use jiff::{Timestamp, Zoned, tz::TimeZone};
-use nu_ansi_term::{Color, Style};
-use tracing_core::{Event, Level, Subscriber, field::Field};
+#[doc(no_inline)]
+pub use nu_ansi_term::{Color, Style};
+use tracing_core::{Event, Level, Metadata, Subscriber, field::Field};
 use tracing_log::NormalizeEvent;
 use tracing_subscriber::{
     field::{MakeVisitor, Visit, VisitFmt, VisitOutput},
     fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, format::Writer},
     registry::LookupSpan,
 };
+use unicode_width::UnicodeWidthStr;
 
 #[cfg(feature = "re-exports")]
 pub use tracing;
@@ -86,8 +93,49 @@ pub mod macros {
     };
 }
 
+#[cfg(feature = "clap")]
+pub mod cli;
+
+mod clock;
+use clock::SharedClock;
+pub use clock::{Clock, FixedClock, ManualClock, SystemClock};
+
 mod config;
-pub use config::{ColorMode, Config, Output};
+pub use config::{ColorMode, Config, Output, Profile};
+
+mod env;
+pub use env::{EnvError, ParseError};
+
+mod panic;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[cfg(unix)]
+mod journald;
+#[cfg(unix)]
+pub use journald::{Journald, JournaldLayer};
+
+mod ring;
+pub use ring::RingBuffer;
+
+mod summary;
+pub use summary::{Counts, summary};
+use summary::{SUMMARY_TARGET, SummaryState};
+
+mod suppress;
+use suppress::{FLUSH_TARGET, Summary, Suppressor};
+
+#[cfg(unix)]
+mod syslog;
+#[cfg(unix)]
+pub use syslog::{Facility, Syslog, SyslogFormat, SyslogWriter};
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+mod writer;
+pub use writer::{Buffered, Guard, NonBlocking, OverflowPolicy};
 
 /// Extension trait for writing ANSI-styled messages.
 trait WriterExt: fmt::Write {
@@ -97,6 +145,10 @@ trait WriterExt: fmt::Write {
     /// write plain output instead.
     fn enable_ansi(&self) -> bool;
 
+    /// A [`Writer`] for formatting fields into this writer. When this isn't a `Writer` already,
+    /// ANSI styles for fields follow the [`EVENT_CONTEXT`] instead.
+    fn fields_writer(&mut self) -> Writer<'_>;
+
     /// Write any `Display`-able type to this Writer, using the given `Style` if and only if
     /// `enable_ansi` returns `true`.
     fn write_style<S, T>(&mut self, style: S, value: T) -> fmt::Result
@@ -118,6 +170,37 @@ impl WriterExt for Writer<'_> {
     fn enable_ansi(&self) -> bool {
         self.has_ansi_escapes()
     }
+
+    #[inline]
+    fn fields_writer(&mut self) -> Writer<'_> {
+        self.by_ref()
+    }
+}
+
+/// Any `fmt::Write` type paired with whether ANSI styles should be used, for when output is being
+/// rendered somewhere other than directly to the [`Writer`] that it's destined for.
+struct Styled<W> {
+    inner: W,
+    ansi: bool,
+}
+
+impl<W: fmt::Write> fmt::Write for Styled<W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_str(s)
+    }
+}
+
+impl<W: fmt::Write> WriterExt for Styled<W> {
+    #[inline]
+    fn enable_ansi(&self) -> bool {
+        self.ansi
+    }
+
+    #[inline]
+    fn fields_writer(&mut self) -> Writer<'_> {
+        Writer::new(&mut self.inner)
+    }
 }
 
 /// Macro to call [`WriterExt::write_style`] with arbitrary format arguments.
@@ -142,17 +225,310 @@ macro_rules! write_style {
 /// are formatted in square brackets and dimmed text style like `[name=value]`. Padding is added on
 /// either side of the `message` field, but not around other fields.
 ///
+/// Field names and values are styled separately, and numbers, booleans, strings, and errors each
+/// have their own value style, see [`with_key_style`] and the other `with_*_style` methods. By
+/// default everything is dimmed except for errors, which are dimmed red. Error fields use the
+/// error style for the whole field, including the brackets and field name.
+///
+/// # Field Layout
+/// The bracketed style can be changed to a [logfmt]-like `name=value` style separated by spaces
+/// using [`with_layout`]. Normally fields are printed in the order they were declared, which for
+/// most events means before or after the message. With [`with_trailing_fields`], all other fields
+/// are moved after the message. With [`with_block_fields`], fields are moved to an indented line
+/// under the message when there are more than a given number of them.
+///
+/// In the logfmt layout, a span's fields are wrapped in curly braces like `span{name=value}` to
+/// set them apart from the span name. An event's fields are never wrapped, even without a message.
+///
+/// # Field Filtering
+/// Fields can be hidden by name, either everywhere with [`with_hidden_fields`] or only for events
+/// with a given target or its sub-targets (`http` matches `http` and `http::client`, but not
+/// `https`) with [`with_hidden_fields_for`] and [`with_only_fields_for`]. When multiple per-target
+/// rules match an event, the one with the longest target wins. The `message` field is never
+/// hidden.
+///
+/// Per-target rules only apply to events formatted by [`EventFormatter`], because that's where the
+/// event's target is known. Span fields are formatted when the span is created and only the global
+/// [`with_hidden_fields`] rules apply to them.
+///
+/// ```
+/// use serif::FieldFormatter;
+///
+/// let fields = FieldFormatter::new()
+///     .with_hidden_fields(["request_id"])
+///     .with_only_fields_for("http", ["user", "path"]);
+/// # let _ = fields;
+/// ```
+///
 /// [`SubscriberBuilder::fmt_fields`]: tracing_subscriber::fmt::SubscriberBuilder::fmt_fields
+/// [`with_hidden_fields`]: FieldFormatter::with_hidden_fields
+/// [`with_hidden_fields_for`]: FieldFormatter::with_hidden_fields_for
+/// [`with_only_fields_for`]: FieldFormatter::with_only_fields_for
+/// [`with_key_style`]: FieldFormatter::with_key_style
+/// [logfmt]: https://brandur.org/logfmt
+/// [`with_layout`]: FieldFormatter::with_layout
+/// [`with_trailing_fields`]: FieldFormatter::with_trailing_fields
+/// [`with_block_fields`]: FieldFormatter::with_block_fields
 #[derive(Clone)]
 pub struct FieldFormatter {
-    // reserve the right to add options in the future
-    _private: (),
+    // Options are shared with every FieldVisitor that's created, so keep them behind an Arc to
+    // make that cheap.
+    options: Arc<FieldOptions>,
+}
+
+/// Private options for [`FieldFormatter`] and [`FieldVisitor`].
+#[derive(Debug, Clone, Default)]
+struct FieldOptions {
+    /// Field names that are hidden for all events and spans.
+    hidden: Vec<Box<str>>,
+    /// Per-target field rules.
+    target_rules: Vec<TargetFieldRule>,
+    /// Styles for the different parts and types of fields.
+    styles: FieldStyles,
+    /// Render `&str` values without quotes when they contain no whitespace.
+    unquoted_strings: bool,
+    /// How fields are rendered and separated.
+    layout: FieldLayout,
+    /// Move all non-message fields after the message.
+    trailing: bool,
+    /// Move fields to an indented line when there are more than this many.
+    block_threshold: Option<usize>,
+}
+
+impl FieldOptions {
+    /// Whether non-message fields need to be buffered until the visitor finishes.
+    fn buffer_fields(&self) -> bool {
+        self.trailing || self.block_threshold.is_some()
+    }
+}
+
+/// How non-message fields are rendered by [`FieldFormatter`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FieldLayout {
+    /// Each field is enclosed in square brackets like `[name=value]`, with no space between
+    /// adjacent fields. This is the default.
+    #[default]
+    Brackets,
+    /// [logfmt]-like `name=value` pairs separated by spaces. Fields without a message, such as a
+    /// span's fields, are wrapped in curly braces.
+    ///
+    /// [logfmt]: https://brandur.org/logfmt
+    Logfmt,
+}
+
+/// ANSI styles used by [`FieldVisitor`] for non-message fields.
+#[derive(Debug, Clone)]
+struct FieldStyles {
+    /// The brackets, field name, and `=` sign.
+    key: Style,
+    /// Values recorded with `Debug`, i.e. everything without a more specific style.
+    value: Style,
+    /// Integer and floating point values.
+    number: Style,
+    /// Boolean values.
+    boolean: Style,
+    /// String values.
+    string: Style,
+    /// Error values.
+    error: Style,
+}
+
+impl Default for FieldStyles {
+    fn default() -> Self {
+        let dimmed = Style::default().dimmed();
+        Self {
+            key: dimmed,
+            value: dimmed,
+            number: dimmed,
+            boolean: dimmed,
+            string: dimmed,
+            error: Color::Red.dimmed(),
+        }
+    }
+}
+
+/// A field filtering rule that applies to events with target `target` or one of its sub-targets.
+#[derive(Debug, Clone)]
+struct TargetFieldRule {
+    target: Box<str>,
+    kind: TargetFieldRuleKind,
+    fields: Vec<Box<str>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetFieldRuleKind {
+    /// Hide the listed fields.
+    Hide,
+    /// Hide everything except the listed fields.
+    Only,
+}
+
+/// Is `target` equal to `parent` or nested under it as a module path, like `parent::child`?
+pub(crate) fn is_sub_target(target: &str, parent: &str) -> bool {
+    target.strip_prefix(parent).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+impl FieldOptions {
+    /// Find the most specific per-target rule for the given event target.
+    fn rule_for(&self, target: &str) -> Option<usize> {
+        self.target_rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| is_sub_target(target, &rule.target))
+            .max_by_key(|(_, rule)| rule.target.len())
+            .map(|(idx, _)| idx)
+    }
+
+    /// Whether the field `name` should be hidden, given the rule chosen by `rule_for`.
+    fn is_hidden(&self, rule: Option<usize>, name: &str) -> bool {
+        if self.hidden.iter().any(|f| **f == *name) {
+            return true;
+        }
+        match rule.map(|idx| &self.target_rules[idx]) {
+            Some(rule) => {
+                let listed = rule.fields.iter().any(|f| **f == *name);
+                match rule.kind {
+                    TargetFieldRuleKind::Hide => listed,
+                    TargetFieldRuleKind::Only => !listed,
+                }
+            }
+            None => false,
+        }
+    }
 }
 
 impl FieldFormatter {
     /// Create a new `FieldFormatter` with the default configuration.
     pub fn new() -> Self {
-        Self { _private: () }
+        Self { options: Default::default() }
+    }
+
+    /// Hide fields with the given names from all events and spans.
+    pub fn with_hidden_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let options = Arc::make_mut(&mut self.options);
+        options.hidden.extend(fields.into_iter().map(|f| f.into().into_boxed_str()));
+        self
+    }
+
+    /// Hide fields with the given names from events with the given target or any of its
+    /// sub-targets, e.g. `app` and `app::db` but not `application`.
+    pub fn with_hidden_fields_for<I, S>(self, target: impl Into<String>, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.with_target_rule(target.into(), TargetFieldRuleKind::Hide, fields)
+    }
+
+    /// Show only the fields with the given names for events with the given target or any of its
+    /// sub-targets.
+    pub fn with_only_fields_for<I, S>(self, target: impl Into<String>, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.with_target_rule(target.into(), TargetFieldRuleKind::Only, fields)
+    }
+
+    fn with_target_rule<I, S>(
+        mut self,
+        target: String,
+        kind: TargetFieldRuleKind,
+        fields: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let rule = TargetFieldRule {
+            target: target.into_boxed_str(),
+            kind,
+            fields: fields.into_iter().map(|f| f.into().into_boxed_str()).collect(),
+        };
+        let options = Arc::make_mut(&mut self.options);
+        // a later rule for the same target replaces the earlier one
+        options.target_rules.retain(|r| r.target != rule.target);
+        options.target_rules.push(rule);
+        self
+    }
+
+    /// Set the style of field names, including the surrounding brackets and `=` sign. The default
+    /// is dimmed.
+    pub fn with_key_style(mut self, style: Style) -> Self {
+        Arc::make_mut(&mut self.options).styles.key = style;
+        self
+    }
+
+    /// Set the style of field values which don't have a more specific type-based style. The
+    /// default is dimmed.
+    pub fn with_value_style(mut self, style: Style) -> Self {
+        Arc::make_mut(&mut self.options).styles.value = style;
+        self
+    }
+
+    /// Set the style of integer and floating point field values. The default is dimmed.
+    pub fn with_number_style(mut self, style: Style) -> Self {
+        Arc::make_mut(&mut self.options).styles.number = style;
+        self
+    }
+
+    /// Set the style of boolean field values. The default is dimmed.
+    pub fn with_bool_style(mut self, style: Style) -> Self {
+        Arc::make_mut(&mut self.options).styles.boolean = style;
+        self
+    }
+
+    /// Set the style of string field values. The default is dimmed.
+    pub fn with_string_style(mut self, style: Style) -> Self {
+        Arc::make_mut(&mut self.options).styles.string = style;
+        self
+    }
+
+    /// Set the style of [`Error`] fields. Unlike the other value styles, this applies to the whole
+    /// field including its name. The default is dimmed red.
+    ///
+    /// [`Error`]: std::error::Error
+    pub fn with_error_style(mut self, style: Style) -> Self {
+        Arc::make_mut(&mut self.options).styles.error = style;
+        self
+    }
+
+    /// Set how non-message fields are rendered. The default is [`FieldLayout::Brackets`].
+    pub fn with_layout(mut self, layout: FieldLayout) -> Self {
+        Arc::make_mut(&mut self.options).layout = layout;
+        self
+    }
+
+    /// Set whether all non-message fields are moved after the message, regardless of the order in
+    /// which they were declared. The default is `false`.
+    pub fn with_trailing_fields(mut self, trailing: bool) -> Self {
+        Arc::make_mut(&mut self.options).trailing = trailing;
+        self
+    }
+
+    /// Move an event's non-message fields to an indented line under the message when there are
+    /// more than `threshold` of them. When there are `threshold` or fewer fields, they're printed
+    /// after the message like [`with_trailing_fields`]. The default is `None`, which disables the
+    /// block layout.
+    ///
+    /// Use `Some(0)` to always put fields on their own line.
+    ///
+    /// [`with_trailing_fields`]: FieldFormatter::with_trailing_fields
+    pub fn with_block_fields(mut self, threshold: Option<usize>) -> Self {
+        Arc::make_mut(&mut self.options).block_threshold = threshold;
+        self
+    }
+
+    /// Set whether string field values are rendered without quotes when they're non-empty and
+    /// contain no whitespace. The default is `false`, which renders all strings using their
+    /// `Debug` implementation like `[name="value"]`.
+    pub fn with_unquoted_strings(mut self, unquoted_strings: bool) -> Self {
+        Arc::make_mut(&mut self.options).unquoted_strings = unquoted_strings;
+        self
     }
 }
 
@@ -172,7 +548,46 @@ impl<'a> MakeVisitor<Writer<'a>> for FieldFormatter {
     type Visitor = FieldVisitor<'a>;
 
     fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
-        FieldVisitor::new(target)
+        let mut visitor = FieldVisitor::with_options(target, self.options.clone());
+        EVENT_CONTEXT.with_borrow(|ctx| {
+            if let Some(ctx) = ctx {
+                visitor.rule = self.options.rule_for(&ctx.target);
+                visitor.ansi = ctx.ansi;
+                visitor.event = true;
+            }
+        });
+        visitor
+    }
+}
+
+/// Information about the event currently being formatted by [`EventFormatter`].
+struct EventContext {
+    /// The event's (normalized) target, used for per-target field rules.
+    target: String,
+    /// Whether ANSI styles are enabled. This can differ from the [`Writer`] given to
+    /// [`FieldFormatter`] when `EventFormatter` renders fields into a temporary buffer.
+    ansi: bool,
+}
+
+thread_local! {
+    /// The event currently being formatted by [`EventFormatter`], if any. This is how
+    /// [`FieldFormatter`] learns about the event, since [`MakeVisitor`] only gets a writer.
+    static EVENT_CONTEXT: RefCell<Option<EventContext>> = const { RefCell::new(None) };
+}
+
+/// Guard which sets [`EVENT_CONTEXT`] and restores the previous value when dropped.
+struct EventContextGuard(Option<EventContext>);
+
+impl EventContextGuard {
+    fn enter(target: &str, ansi: bool) -> Self {
+        let ctx = EventContext { target: target.to_owned(), ansi };
+        Self(EVENT_CONTEXT.replace(Some(ctx)))
+    }
+}
+
+impl Drop for EventContextGuard {
+    fn drop(&mut self) {
+        EVENT_CONTEXT.set(self.0.take());
     }
 }
 
@@ -189,7 +604,8 @@ enum FieldType {
 /// If a field is named `message`, then it's printed in the default text style. All other fields
 /// are formatted in square brackets and dimmed text style like `[name=value]`. Padding is added on
 /// either side of the `message` field, but not around other fields. [`Error`] typed fields are
-/// rendered in dimmed red text.
+/// rendered in dimmed red text. The styles of field names and of each type of value can be
+/// customized using [`FieldFormatter`]'s builder methods.
 ///
 /// [`Error`]: std::error::Error
 #[derive(Debug)]
@@ -197,87 +613,246 @@ pub struct FieldVisitor<'a> {
     writer: Writer<'a>,
     result: fmt::Result,
     last: FieldType,
+    options: Arc<FieldOptions>,
+    rule: Option<usize>,
+    /// Whether to use ANSI styles.
+    ansi: bool,
+    /// Whether an event's fields are being formatted, rather than a span's.
+    event: bool,
+    /// Rendered non-message fields, when they're being buffered until the end.
+    pending: String,
+    /// Number of fields in `pending`.
+    pending_count: usize,
 }
 
+/// Indentation for fields written using the block layout.
+const BLOCK_INDENT: &str = "    ";
+
 impl<'a> FieldVisitor<'a> {
     /// Create a new `FieldVisitor` with the given writer.
     pub fn new(writer: Writer<'a>) -> Self {
-        Self { writer, result: Ok(()), last: FieldType::None }
+        Self::with_options(writer, Default::default())
     }
 
-    /// Get the padding that should be prepended when visiting the message field
-    fn pad_for_message(&self) -> &'static str {
-        match self.last {
-            FieldType::None => "",
-            FieldType::Message | FieldType::Other => " ",
+    fn with_options(writer: Writer<'a>, options: Arc<FieldOptions>) -> Self {
+        Self {
+            ansi: writer.has_ansi_escapes(),
+            event: false,
+            writer,
+            result: Ok(()),
+            last: FieldType::None,
+            options,
+            rule: None,
+            pending: String::new(),
+            pending_count: 0,
         }
     }
 
-    /// Get the padding that should be prepended when visiting a non-message field
-    fn pad_for_other(&self) -> &'static str {
-        match self.last {
-            FieldType::Message => " ",
-            FieldType::None | FieldType::Other => "",
+    /// Whether a field should be skipped entirely.
+    fn skip_field(&self, name: &str) -> bool {
+        if name.starts_with("log.") {
+            // skip log metadata
+            return true;
         }
+        name != "message" && self.options.is_hidden(self.rule, name)
     }
-}
 
-impl Visit for FieldVisitor<'_> {
-    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    /// Write a field, using `style` for the value unless this is the message field.
+    fn record_styled(&mut self, field: &Field, style: Style, value: impl fmt::Display) {
+        self.record_field(field, self.options.styles.key, style, value);
+    }
+
+    /// Write a field, using `key_style` for the brackets, name, and `=` sign, and `style` for
+    /// the value, unless this is the message field.
+    fn record_field(
+        &mut self,
+        field: &Field,
+        key_style: Style,
+        style: Style,
+        value: impl fmt::Display,
+    ) {
         if self.result.is_err() {
             return;
         }
 
         let name = field.name();
-        if name.starts_with("log.") {
-            // skip log metadata
+        if self.skip_field(name) {
             return;
         }
 
         self.result = if name == "message" {
-            let pad = self.pad_for_message();
-            self.last = FieldType::Message;
-            write!(self.writer, "{pad}{value:?}")
+            self.write_message(value)
+        } else if self.options.buffer_fields() || self.braces_pending() {
+            // Logfmt span fields are held back, because if no message shows up they get wrapped
+            // in braces.
+            let pad = if self.pending_count == 0 { "" } else { self.field_separator() };
+            self.pending_count += 1;
+            let mut pending = Styled { inner: &mut self.pending, ansi: self.ansi };
+            write_key_value(&mut pending, self.options.layout, pad, name, key_style, style, value)
         } else {
             let pad = self.pad_for_other();
             self.last = FieldType::Other;
-            write_style!(self.writer, Style::default().dimmed(), "{pad}[{name}={value:?}]")
+            let mut writer = Styled { inner: &mut self.writer, ansi: self.ansi };
+            write_key_value(&mut writer, self.options.layout, pad, name, key_style, style, value)
         };
     }
 
+    /// Write the message field, along with any fields that were held back to go before it.
+    fn write_message(&mut self, value: impl fmt::Display) -> fmt::Result {
+        if self.pending_count > 0 && !self.options.buffer_fields() {
+            self.writer.write_str(&self.pending)?;
+            self.pending.clear();
+            self.pending_count = 0;
+            self.last = FieldType::Other;
+        }
+
+        let pad = self.pad_for_message();
+        self.last = FieldType::Message;
+        write!(self.writer, "{pad}{value}")
+    }
+
+    /// Write out any buffered fields.
+    fn write_pending(&mut self) -> fmt::Result {
+        if self.pending_count == 0 {
+            return Ok(());
+        }
+
+        if self.braces_pending() {
+            return write!(self.writer, "{{{}}}", self.pending);
+        }
+
+        let block = self.last == FieldType::Message
+            && self.options.block_threshold.is_some_and(|n| self.pending_count > n);
+        if block {
+            write!(self.writer, "\n{BLOCK_INDENT}{}", self.pending)
+        } else {
+            let pad = if self.last == FieldType::None { "" } else { " " };
+            write!(self.writer, "{pad}{}", self.pending)
+        }
+    }
+
+    /// Whether the fields seen so far should be wrapped in braces, which is the case for logfmt
+    /// fields without a message, except in events.
+    fn braces_pending(&self) -> bool {
+        self.options.layout == FieldLayout::Logfmt && self.last == FieldType::None && !self.event
+    }
+
+    /// Get the separator between two adjacent non-message fields.
+    fn field_separator(&self) -> &'static str {
+        match self.options.layout {
+            FieldLayout::Brackets => "",
+            FieldLayout::Logfmt => " ",
+        }
+    }
+
+    /// Get the padding that should be prepended when visiting the message field
+    fn pad_for_message(&self) -> &'static str {
+        match self.last {
+            FieldType::None => "",
+            FieldType::Message | FieldType::Other => " ",
+        }
+    }
+
+    /// Get the padding that should be prepended when visiting a non-message field
+    fn pad_for_other(&self) -> &'static str {
+        match self.last {
+            FieldType::None => "",
+            FieldType::Message => " ",
+            FieldType::Other => self.field_separator(),
+        }
+    }
+}
+
+/// Write a non-message field like `[name=value]` or `name=value` depending on the layout.
+///
+/// When the key and value styles are the same, the whole field is written with a single style so
+/// that the output only has one pair of escape sequences.
+fn write_key_value<W: WriterExt>(
+    writer: &mut W,
+    layout: FieldLayout,
+    pad: &str,
+    name: &str,
+    key_style: Style,
+    style: Style,
+    value: impl fmt::Display,
+) -> fmt::Result {
+    match layout {
+        FieldLayout::Brackets if key_style == style => {
+            write_style!(writer, style, "{pad}[{name}={value}]")
+        }
+        FieldLayout::Logfmt if key_style == style => {
+            write_style!(writer, style, "{pad}{name}={value}")
+        }
+        FieldLayout::Brackets => {
+            write_style!(writer, key_style, "{pad}[{name}=")?;
+            writer.write_style(style, value)?;
+            writer.write_style(key_style, ']')
+        }
+        FieldLayout::Logfmt => {
+            write_style!(writer, key_style, "{pad}{name}=")?;
+            writer.write_style(style, value)
+        }
+    }
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        // Debug always includes a decimal point, so that `1.0` isn't rendered like an integer
+        self.record_styled(field, self.options.styles.number, format_args!("{value:?}"));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_styled(field, self.options.styles.number, value);
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_styled(field, self.options.styles.number, value);
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.record_styled(field, self.options.styles.number, value);
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.record_styled(field, self.options.styles.number, value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_styled(field, self.options.styles.boolean, value);
+    }
+
     fn record_str(&mut self, field: &Field, value: &str) {
+        let style = self.options.styles.string;
         if field.name() == "message" {
             // Usually the message gets visited by record_debug, presumably becuase it's
             // a fmt::Arguments object from a format_args! macro, but just in case the message
-            // field ends up here, force using the Display impl to render without quotes.
-            self.record_debug(field, &format_args!("{value}"));
+            // field ends up here, use the Display impl to render without quotes.
+            self.record_styled(field, style, value);
+        } else if self.options.unquoted_strings
+            && !value.is_empty()
+            && !value.contains(char::is_whitespace)
+        {
+            self.record_styled(field, style, value);
         } else {
-            // Otherwise, delegate to record_debug as usual.
-            self.record_debug(field, &value);
+            self.record_styled(field, style, format_args!("{value:?}"));
         }
     }
 
     fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
-        if self.result.is_err() {
-            return;
-        }
-
-        let name = field.name();
-        if name.starts_with("log.") {
-            // skip log metadata
-            return;
-        }
+        // Treat Errors like a non-message field, and make the whole field red.
+        let style = self.options.styles.error;
+        self.record_field(field, style, style, value);
+    }
 
-        // Treat Errors like a non-message field, and make them red.
-        let pad = self.pad_for_other();
-        self.last = FieldType::Other;
-        self.result = write_style!(self.writer, Color::Red.dimmed(), "{pad}[{name}={value}]");
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_styled(field, self.options.styles.value, format_args!("{value:?}"));
     }
 }
 
 impl VisitOutput<fmt::Result> for FieldVisitor<'_> {
-    fn finish(self) -> fmt::Result {
-        self.result
+    fn finish(mut self) -> fmt::Result {
+        self.result?;
+        self.write_pending()
     }
 }
 
@@ -349,18 +924,10 @@ impl TimeFormat {
     /// that no unknown `%` fields are present. In release mode, formatting the timestamp fails and
     /// tracing-subscriber will emit "Unable to format the following event" messages.
     pub fn local_custom(format: impl Into<String>) -> Self {
-        let format = format.into();
-
+        let this = Self::local_custom_unchecked(format);
         #[cfg(debug_assertions)]
-        {
-            let zoned = Zoned::new(Timestamp::UNIX_EPOCH, TimeZone::UTC);
-            let res = jiff::fmt::strtime::format(format.as_bytes(), &zoned);
-            if let Err(err) = res {
-                panic!("Unable to use custom TimeFormat '{format}': {err}");
-            }
-        }
-
-        Self { inner: InnerTimeFormat::Local(Some(format.into_boxed_str())) }
+        this.assert_valid();
+        this
     }
 
     /// Render a timestamp in UTC using a custom format.
@@ -369,17 +936,41 @@ impl TimeFormat {
     /// that no unknown `%` fields are present. In release mode, formatting the timestamp fails and
     /// tracing-subscriber will emit "Unable to format the following event" messages.
     pub fn utc_custom(format: impl Into<String>) -> Self {
-        let format = format.into();
-
+        let this = Self::utc_custom_unchecked(format);
         #[cfg(debug_assertions)]
-        {
-            let res = jiff::fmt::strtime::format(format.as_bytes(), Timestamp::UNIX_EPOCH);
-            if let Err(err) = res {
-                panic!("Unable to use custom TimeFormat '{format}': {err}");
+        this.assert_valid();
+        this
+    }
+
+    pub(crate) fn local_custom_unchecked(format: impl Into<String>) -> Self {
+        Self { inner: InnerTimeFormat::Local(Some(format.into().into_boxed_str())) }
+    }
+
+    pub(crate) fn utc_custom_unchecked(format: impl Into<String>) -> Self {
+        Self { inner: InnerTimeFormat::Utc(Some(format.into().into_boxed_str())) }
+    }
+
+    /// Check that a custom format string contains no unknown `%` fields by rendering the epoch.
+    pub(crate) fn validate(&self) -> Result<(), jiff::Error> {
+        match &self.inner {
+            InnerTimeFormat::None | InnerTimeFormat::Local(None) | InnerTimeFormat::Utc(None) => {
+                Ok(())
+            }
+            InnerTimeFormat::Local(Some(format)) => {
+                let zoned = Zoned::new(Timestamp::UNIX_EPOCH, TimeZone::UTC);
+                jiff::fmt::strtime::format(format.as_bytes(), &zoned).map(|_| ())
+            }
+            InnerTimeFormat::Utc(Some(format)) => {
+                jiff::fmt::strtime::format(format.as_bytes(), Timestamp::UNIX_EPOCH).map(|_| ())
             }
         }
+    }
 
-        Self { inner: InnerTimeFormat::Utc(Some(format.into_boxed_str())) }
+    #[cfg(debug_assertions)]
+    fn assert_valid(&self) {
+        if let Err(err) = self.validate() {
+            panic!("Unable to use custom {self:?}: {err}");
+        }
     }
 
     /// Get a [`Display`]-able object of this format applied to a `Timestamp`.
@@ -391,7 +982,12 @@ impl TimeFormat {
 
     /// Render the current system time in this format
     pub fn render_now(&self) -> impl fmt::Display + '_ {
-        self.render(Timestamp::now())
+        self.render_from(&SystemClock)
+    }
+
+    /// Render the current time of a [`Clock`] in this format.
+    pub fn render_from<C: Clock + ?Sized>(&self, clock: &C) -> impl fmt::Display + '_ {
+        self.render(clock.now())
     }
 
     fn is_none(&self) -> bool {
@@ -421,23 +1017,444 @@ impl fmt::Display for TimeDisplay<'_> {
     }
 }
 
+/// The text used to display each log level.
+///
+/// Labels are right-aligned to the width of the longest label, so that messages line up. Widths are
+/// measured in terminal columns, so emoji labels that are two columns wide line up too.
+#[derive(Debug, Clone)]
+pub struct LevelLabels {
+    /// Labels in the order TRACE, DEBUG, INFO, WARN, ERROR.
+    labels: [Cow<'static, str>; 5],
+    /// Number of terminal columns taken up by the longest label.
+    width: usize,
+}
+
+impl LevelLabels {
+    /// The full uppercase level names, like `INFO` and `ERROR`. This is the default.
+    pub const fn full() -> Self {
+        Self {
+            labels: [
+                Cow::Borrowed("TRACE"),
+                Cow::Borrowed("DEBUG"),
+                Cow::Borrowed("INFO"),
+                Cow::Borrowed("WARN"),
+                Cow::Borrowed("ERROR"),
+            ],
+            width: 5,
+        }
+    }
+
+    /// Single letter level names: `T`, `D`, `I`, `W`, and `E`.
+    pub const fn short() -> Self {
+        Self {
+            labels: [
+                Cow::Borrowed("T"),
+                Cow::Borrowed("D"),
+                Cow::Borrowed("I"),
+                Cow::Borrowed("W"),
+                Cow::Borrowed("E"),
+            ],
+            width: 1,
+        }
+    }
+
+    /// Custom level labels, such as emoji or icons.
+    pub fn custom(
+        trace: impl Into<Cow<'static, str>>,
+        debug: impl Into<Cow<'static, str>>,
+        info: impl Into<Cow<'static, str>>,
+        warn: impl Into<Cow<'static, str>>,
+        error: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let labels = [trace.into(), debug.into(), info.into(), warn.into(), error.into()];
+        let width = labels.iter().map(|l| visible_width(l)).max().unwrap_or(0);
+        Self { labels, width }
+    }
+
+    /// Write the label for a level, right-aligned and followed by a space. This pads by terminal
+    /// columns, unlike `format!` width specifiers which count characters.
+    fn write(&self, writer: &mut impl WriterExt, level: Level) -> fmt::Result {
+        let label = self.get(level);
+        let pad = self.width.saturating_sub(visible_width(label));
+        write_style!(writer, level_color(level), "{:pad$}{label} ", "")
+    }
+
+    /// Get the label for a level.
+    fn get(&self, level: Level) -> &str {
+        let idx = match level {
+            Level::TRACE => 0,
+            Level::DEBUG => 1,
+            Level::INFO => 2,
+            Level::WARN => 3,
+            Level::ERROR => 4,
+        };
+        &self.labels[idx]
+    }
+}
+
+impl Default for LevelLabels {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+/// Map a tracing level to a syslog severity, which is also used for sd-daemon level prefixes.
+pub(crate) fn severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+/// The color used to display a level.
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::TRACE => Color::Purple,
+        Level::DEBUG => Color::Blue,
+        Level::INFO => Color::Green,
+        Level::WARN => Color::Yellow,
+        Level::ERROR => Color::Red,
+    }
+}
+
+/// Shorten a module path by abbreviating all but the last component to its first character, e.g.
+/// `my_crate::net::http::client` becomes `m::n::h::client`.
+fn abbreviate_target(target: &str) -> Cow<'_, str> {
+    let Some((parents, last)) = target.rsplit_once("::") else {
+        return Cow::Borrowed(target);
+    };
+
+    let mut short = String::with_capacity(target.len());
+    for part in parents.split("::") {
+        short.extend(part.chars().next());
+        short.push_str("::");
+    }
+    short.push_str(last);
+    Cow::Owned(short)
+}
+
+/// Iterate over the characters in a string that will be visible on a terminal, skipping ANSI
+/// escape sequences.
+fn visible_chars(s: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = s.chars();
+    std::iter::from_fn(move || {
+        loop {
+            let c = chars.next()?;
+            if c != '\x1b' {
+                return Some(c);
+            }
+            // skip a CSI sequence, which is ESC '[' followed by parameters and a final byte in the
+            // range '@'..='~'
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Count the number of terminal columns that a string will take up, ignoring ANSI escape
+/// sequences.
+fn visible_width(s: &str) -> usize {
+    if s.contains('\x1b') { visible_chars(s).collect::<String>().width() } else { s.width() }
+}
+
+/// Write `text`, soft-wrapping at spaces so that lines are no longer than `width` columns when
+/// possible. The text starts at column `indent`, and wrapped lines are indented to line up with it,
+/// as are lines after explicit newlines in the text. Words that are too long to fit on a line by
+/// themselves are not broken up.
+fn write_wrapped(
+    writer: &mut impl fmt::Write,
+    text: &str,
+    indent: usize,
+    width: usize,
+) -> fmt::Result {
+    let mut col = indent;
+    let mut line_start = true;
+    // whether the indent still needs to be written after an explicit newline, which is deferred
+    // so that empty lines don't get trailing whitespace
+    let mut need_indent = false;
+    let mut spaces = 0;
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('\n') {
+            writer.write_char('\n')?;
+            col = indent;
+            line_start = true;
+            need_indent = true;
+            spaces = 0;
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix(' ') {
+            spaces += 1;
+            rest = r;
+        } else {
+            let end = rest.find([' ', '\n']).unwrap_or(rest.len());
+            let (word, r) = rest.split_at(end);
+            let word_width = visible_width(word);
+            if need_indent {
+                write!(writer, "{:indent$}", "")?;
+                need_indent = false;
+            }
+            if !line_start && col + spaces + word_width > width {
+                write!(writer, "\n{:indent$}", "")?;
+                col = indent;
+            } else {
+                write!(writer, "{:spaces$}", "")?;
+                col += spaces;
+            }
+            writer.write_str(word)?;
+            col += word_width;
+            line_start = false;
+            spaces = 0;
+            rest = r;
+        }
+    }
+
+    write!(writer, "{:spaces$}", "")
+}
+
+/// Truncate a string from the left to at most `max` characters, using `…` to show that it's been
+/// truncated.
+fn truncate_left(s: &str, max: usize) -> Cow<'_, str> {
+    let len = s.chars().count();
+    if len <= max {
+        return Cow::Borrowed(s);
+    }
+    let skip = len - max.saturating_sub(1);
+    let mut short = String::from("…");
+    short.extend(s.chars().skip(skip));
+    Cow::Owned(short)
+}
+
+/// The width of the column containing an event's span scope and target.
+///
+/// When messages are aligned, the span scope and target are padded with spaces so that every
+/// message starts in the same column. Targets which are too long to fit are truncated from the
+/// left, so that the most specific part of the module path stays visible. If the span scope is
+/// still too wide once the target has been truncated, then it's truncated from the left as well.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TargetWidth {
+    /// Don't pad or truncate anything. This is the default.
+    #[default]
+    Natural,
+    /// Pad to a fixed width.
+    Fixed(usize),
+    /// Pad to the width of the longest span scope and target seen so far, up to `max`.
+    Adaptive {
+        /// The maximum width to pad to.
+        max: usize,
+    },
+}
+
+/// Don't bother wrapping messages unless there's at least this much space for them.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// Preset output formats for [`EventFormatter`], which can be selected using
+/// [`Config::with_format`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Format {
+    /// The full format with all the details. This is the default, see [`EventFormatter::new`].
+    #[default]
+    Full,
+    /// A compact format for narrow terminals, see [`EventFormatter::compact`].
+    Compact,
+}
+
+impl Format {
+    /// Create an `EventFormatter` using this format preset.
+    pub fn event_formatter(self) -> EventFormatter {
+        EventFormatter::new().with_format(self)
+    }
+}
+
 /// Serif's tracing event formatter.
 ///
 /// # Event Format
 /// Events are rendered similarly to [`tracing_subscriber::fmt::format::Full`], but with everything
 /// besides the main log message in dimmed ANSI text colors to increase readability of the main log
 /// message.
+///
+/// For narrow terminals, [`EventFormatter::compact`] provides a preset which uses single-letter
+/// levels, abbreviated targets, and a shorter timestamp.
 #[derive(Debug, Clone)]
 pub struct EventFormatter {
     time_format: TimeFormat,
     display_target: bool,
     display_scope: bool,
+    level_labels: LevelLabels,
+    short_target: bool,
+    target_width: TargetWidth,
+    wrap_width: Option<usize>,
+    level_prefix: bool,
+    /// Summaries of suppressed events to write, see [`Config::with_dedup`].
+    suppressor: Option<Arc<Suppressor>>,
+    /// The end-of-run summary state, whose kept lines are written after the summary event, see
+    /// [`Config::with_summary`].
+    summary: Option<Arc<SummaryState>>,
+    /// Whether to keep recent warnings and errors in `summary`.
+    keep_lines: bool,
+    /// The widest scope and target seen so far, for [`TargetWidth::Adaptive`]. This is shared
+    /// between clones.
+    seen_width: Arc<AtomicUsize>,
+    clock: SharedClock,
 }
 
 impl EventFormatter {
     /// Create a new `EventFormatter` with the default options.
     pub fn new() -> Self {
-        Self { time_format: Default::default(), display_target: true, display_scope: true }
+        Self {
+            time_format: Default::default(),
+            display_target: true,
+            display_scope: true,
+            level_labels: LevelLabels::full(),
+            short_target: false,
+            target_width: TargetWidth::Natural,
+            wrap_width: None,
+            level_prefix: false,
+            suppressor: None,
+            summary: None,
+            keep_lines: false,
+            seen_width: Default::default(),
+            clock: Default::default(),
+        }
+    }
+
+    /// Create a new `EventFormatter` with the compact preset.
+    ///
+    /// This uses [`LevelLabels::short`], abbreviated targets (see [`with_short_target`]), and
+    /// a local timestamp with the format `%H:%M:%S`. Other options are the same as
+    /// [`EventFormatter::new`].
+    ///
+    /// [`with_short_target`]: EventFormatter::with_short_target
+    pub fn compact() -> Self {
+        Self::new().with_format(Format::Compact)
+    }
+
+    /// Apply a preset output format, which sets the level labels, target abbreviation, and
+    /// timestamp format. Other options are unchanged.
+    pub fn with_format(self, format: Format) -> Self {
+        match format {
+            Format::Full => self
+                .with_level_labels(LevelLabels::full())
+                .with_short_target(false)
+                .with_timestamp(TimeFormat::default()),
+            Format::Compact => self
+                .with_level_labels(LevelLabels::short())
+                .with_short_target(true)
+                .with_timestamp(TimeFormat::local_custom("%H:%M:%S")),
+        }
+    }
+
+    /// Set the labels used to display each level. The default is [`LevelLabels::full`].
+    pub fn with_level_labels(self, level_labels: LevelLabels) -> Self {
+        Self { level_labels, ..self }
+    }
+
+    /// Set whether targets are abbreviated by shortening their module paths, so that
+    /// `my_crate::net::http::client` is displayed as `m::n::h::client`. The default is `false`.
+    pub fn with_short_target(self, short_target: bool) -> Self {
+        Self { short_target, ..self }
+    }
+
+    /// Set the width of the span scope and target column, to align messages. The default is
+    /// [`TargetWidth::Natural`], which doesn't align anything.
+    pub fn with_target_width(self, target_width: TargetWidth) -> Self {
+        Self { target_width, ..self }
+    }
+
+    /// Set the [`Clock`] used to get the current time for timestamps. The default is
+    /// [`SystemClock`].
+    ///
+    /// This is mainly useful for tests that need predictable timestamps, see [`FixedClock`] and
+    /// [`ManualClock`]. Closures which return a [`Timestamp`] can be used as a clock too.
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self { clock: SharedClock::new(clock), ..self }
+    }
+
+    /// Soft-wrap messages and fields at word boundaries so that lines are no longer than `width`
+    /// columns. Wrapped lines, and lines after newlines in the message, are indented to line up
+    /// with the start of the message. The default is `None`, which disables wrapping.
+    ///
+    /// Wrapping is skipped for events where the message would start too close to the right edge.
+    /// Use [`Config::with_wrap`] to automatically wrap to the width of the terminal.
+    pub fn with_wrap_width(self, wrap_width: Option<usize>) -> Self {
+        Self { wrap_width, ..self }
+    }
+
+    /// Set whether each event starts with an [sd-daemon] level prefix like `<4>`, which systemd
+    /// uses as the priority when reading a service's stdout or stderr into the journal. The
+    /// prefix is the same syslog severity that [`Syslog`] uses for each level. The default is
+    /// `false`.
+    ///
+    /// systemd reads each line separately, so continuation lines of multi-line events get the
+    /// default priority.
+    ///
+    /// [sd-daemon]: https://www.freedesktop.org/software/systemd/man/latest/sd-daemon.html
+    /// [`Syslog`]: crate::Syslog
+    pub fn with_level_prefix(self, level_prefix: bool) -> Self {
+        Self { level_prefix, ..self }
+    }
+
+    /// Write summaries of suppressed events from this suppressor before each event.
+    pub(crate) fn with_suppressor(self, suppressor: Arc<Suppressor>) -> Self {
+        Self { suppressor: Some(suppressor), ..self }
+    }
+
+    /// Track the widest scope and target for [`TargetWidth::Adaptive`] separately from other
+    /// clones, for a layer which sees different events.
+    pub(crate) fn with_own_seen_width(self) -> Self {
+        Self { seen_width: Default::default(), ..self }
+    }
+
+    /// Write the lines kept in this summary state after the summary event. If `keep_lines` is
+    /// true, keep recent warnings and errors in it too.
+    pub(crate) fn with_summary(self, summary: Arc<SummaryState>, keep_lines: bool) -> Self {
+        Self { summary: Some(summary), keep_lines, ..self }
+    }
+
+    /// Write a summary of suppressed events as its own line, with a dimmed message. Scopes and
+    /// alignment are skipped since the summary isn't part of any span.
+    fn write_summary(&self, writer: &mut Writer<'_>, summary: &Summary) -> fmt::Result {
+        if self.level_prefix {
+            write!(writer, "<{}>", severity(&summary.level))?;
+        }
+        if !self.time_format.is_none() {
+            let timestamp = self.time_format.render_from(&self.clock);
+            write_style!(writer, Style::default().dimmed(), "{timestamp} ")?;
+        }
+        self.level_labels.write(writer, summary.level)?;
+        if self.display_target {
+            let target = if self.short_target {
+                abbreviate_target(&summary.target)
+            } else {
+                summary.target.as_str().into()
+            };
+            write_style!(writer, Color::Blue.dimmed(), "{target}")?;
+            writer.write_str(": ")?;
+        }
+        write_style!(writer, Style::default().dimmed(), "{}", summary.text)?;
+        writeln!(writer)
+    }
+
+    /// Get the column width to align to, given the natural width of the current event's scope and
+    /// target.
+    fn column_width(&self, natural: usize) -> Option<usize> {
+        match self.target_width {
+            TargetWidth::Natural => None,
+            TargetWidth::Fixed(width) => Some(width),
+            TargetWidth::Adaptive { max } => {
+                let natural = natural.min(max);
+                let prev = self.seen_width.fetch_max(natural, Ordering::Relaxed);
+                Some(prev.max(natural))
+            }
+        }
     }
 
     /// Set the timestamp format for this event formatter.
@@ -477,51 +1494,145 @@ where
         let norm_meta = event.normalized_metadata();
         let meta = norm_meta.as_ref().unwrap_or_else(|| event.metadata());
 
-        // display the timestamp
+        // summaries of suppressed events go before whichever event is logged next
+        if let Some(suppressor) = &self.suppressor {
+            for summary in suppressor.take_summaries() {
+                self.write_summary(&mut writer, &summary)?;
+            }
+        }
+        if meta.target() == FLUSH_TARGET {
+            return Ok(());
+        }
+
+        match &self.summary {
+            // keep a plain copy of warnings and errors for the end-of-run summary
+            Some(summary)
+                if self.keep_lines
+                    && summary.keeps(*meta.level())
+                    && meta.target() != SUMMARY_TARGET =>
+            {
+                let mut line = String::new();
+                let ansi = writer.has_ansi_escapes();
+                self.write_event(ctx, &mut Styled { inner: &mut line, ansi }, event, meta)?;
+                writer.write_str(&line)?;
+                summary.keep_line(visible_chars(&line).collect());
+                Ok(())
+            }
+            // the end-of-run summary is followed by the kept lines
+            Some(summary) if meta.target() == SUMMARY_TARGET => {
+                self.write_event(ctx, &mut writer, event, meta)?;
+                summary.lines().iter().try_for_each(|line| writer.write_str(line))
+            }
+            _ => self.write_event(ctx, &mut writer, event, meta),
+        }
+    }
+}
+
+impl EventFormatter {
+    /// Format a single event, the main part of [`FormatEvent::format_event`].
+    fn write_event<S, N>(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        writer: &mut impl WriterExt,
+        event: &Event<'_>,
+        meta: &Metadata<'_>,
+    ) -> fmt::Result
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> FormatFields<'a> + 'static,
+    {
+        // the sd-daemon prefix is stripped by systemd, so it isn't counted for wrapping
+        if self.level_prefix {
+            write!(writer, "<{}>", severity(meta.level()))?;
+        }
+
+        // display the timestamp, keeping track of the column where the message will start for
+        // wrapping purposes
+        let mut message_col = 0;
         if !self.time_format.is_none() {
-            write_style!(writer, Style::default().dimmed(), "{} ", self.time_format.render_now(),)?;
+            let timestamp = self.time_format.render_from(&self.clock).to_string();
+            message_col += timestamp.chars().count() + 1;
+            write_style!(writer, Style::default().dimmed(), "{timestamp} ")?;
         }
 
         // display the level
-        let level = *meta.level();
-        let level_style = match level {
-            Level::TRACE => Color::Purple,
-            Level::DEBUG => Color::Blue,
-            Level::INFO => Color::Green,
-            Level::WARN => Color::Yellow,
-            Level::ERROR => Color::Red,
-        };
-        write_style!(writer, level_style, "{level:>5} ")?;
+        self.level_labels.write(writer, *meta.level())?;
+        message_col += self.level_labels.width + 1;
 
-        // display the span's scope
+        // render the span's scope, keeping track of how wide it is for alignment
+        let ansi = writer.enable_ansi();
+        let mut scope = String::new();
         let maybe_scope = if self.display_scope { ctx.event_scope() } else { None };
-        if let Some(scope) = maybe_scope {
-            let mut seen = false;
-
-            for span in scope.from_root() {
-                writer.write_style(Color::Cyan.dimmed(), span.metadata().name())?;
-                seen = true;
-
+        if let Some(spans) = maybe_scope {
+            for span in spans.from_root() {
+                let name = span.metadata().name();
+                Styled { inner: &mut scope, ansi }.write_style(Color::Cyan.dimmed(), name)?;
                 if let Some(fields) = span.extensions().get::<FormattedFields<N>>() {
                     if !fields.is_empty() {
-                        write!(writer, "{}:", fields)?;
+                        scope.push_str(fields);
+                        scope.push(':');
                     }
                 }
             }
-
-            if seen {
-                writer.write_char(' ')?;
+            if !scope.is_empty() {
+                scope.push(' ');
+            }
+        }
+        let mut scope_width = visible_width(&scope);
+
+        // the target is the rust module path by default, but can be overridden
+        let mut target =
+            if self.short_target { abbreviate_target(meta.target()) } else { meta.target().into() };
+        let target_width = |t: &str| if self.display_target { visible_width(t) + 2 } else { 0 };
+        let column = self.column_width(scope_width + target_width(&target));
+        if let Some(column) = column {
+            // Truncate the target if it doesn't fit, but always leave the "…" and at least one
+            // character of the target visible.
+            if self.display_target {
+                let avail = column.saturating_sub(scope_width + 2).max(2);
+                if let Cow::Owned(short) = truncate_left(&target, avail) {
+                    target = Cow::Owned(short);
+                }
+            }
+            // If the scope is still too wide, truncate it from the left too, leaving at least the
+            // "…" and the trailing space. Field styles are lost when it's truncated.
+            let avail = column.saturating_sub(target_width(&target)).max(2);
+            if scope_width > avail {
+                let plain: String = visible_chars(&scope).collect();
+                let short = truncate_left(plain.trim_end(), avail - 1);
+                scope.clear();
+                Styled { inner: &mut scope, ansi }.write_style(Color::Cyan.dimmed(), short)?;
+                scope.push(' ');
+                scope_width = avail;
             }
         }
 
-        // display the target (which is the rust module path by default, but can be overridden)
+        // display the scope and target
+        writer.write_str(&scope)?;
         if self.display_target {
-            write_style!(writer, Color::Blue.dimmed(), "{}", meta.target())?;
+            write_style!(writer, Color::Blue.dimmed(), "{target}")?;
             writer.write_str(": ")?;
         }
 
+        // pad out to the aligned message column
+        let used = scope_width + target_width(&target);
+        if let Some(column) = column {
+            let pad = column.saturating_sub(used);
+            write!(writer, "{:pad$}", "")?;
+            message_col += pad;
+        }
+        message_col += used;
+
         // display the event message and fields
-        ctx.format_fields(writer.by_ref(), event)?;
+        let _ctx_guard = EventContextGuard::enter(meta.target(), ansi);
+        match self.wrap_width {
+            Some(wrap_width) if wrap_width >= message_col + MIN_WRAP_WIDTH => {
+                let mut buf = String::new();
+                ctx.format_fields(Writer::new(&mut buf), event)?;
+                write_wrapped(writer, &buf, message_col, wrap_width)?;
+            }
+            _ => ctx.format_fields(writer.fields_writer(), event)?,
+        }
         writeln!(writer)
     }
 }
@@ -0,0 +1,47 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! A panic hook which logs panics through tracing, enabled by `Config::with_panic_hook`.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic::{self, PanicHookInfo};
+use std::thread;
+
+use tracing::field::display;
+
+/// Install a panic hook which emits an ERROR event for the panic, then calls the previously
+/// installed hook.
+pub(crate) fn install_hook() {
+    let prev = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log_panic(info);
+        prev(info);
+    }));
+}
+
+/// Emit an ERROR event with target `panic` describing a panic.
+fn log_panic(info: &PanicHookInfo<'_>) {
+    let payload = info.payload();
+    let message = match payload.downcast_ref::<&str>() {
+        Some(s) => s,
+        None => match payload.downcast_ref::<String>() {
+            Some(s) => s.as_str(),
+            None => "Box<dyn Any>",
+        },
+    };
+
+    let thread = thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+
+    // Backtrace::capture is controlled by the RUST_BACKTRACE and RUST_LIB_BACKTRACE environment
+    // variables, so only include the backtrace field if it's actually been enabled.
+    let backtrace = Backtrace::capture();
+    let backtrace = (backtrace.status() == BacktraceStatus::Captured).then(|| display(&backtrace));
+
+    tracing::error!(
+        target: "panic",
+        thread,
+        location = info.location().map(display),
+        backtrace,
+        "{message}",
+    );
+}
@@ -0,0 +1,155 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! An in-memory ring buffer of recent formatted events. This module is private, but its pub types
+//! are exported and inlined at the top-level of the `serif` crate.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A ring buffer of the most recent formatted events, for dumping debug logs after a failure.
+///
+/// Use [`Config::with_ring_buffer`](crate::Config::with_ring_buffer) to record events at a more
+/// verbose level than the console, then [`drain`](RingBuffer::drain) the buffer or
+/// [`write_to_file`](RingBuffer::write_to_file) from an error path or panic hook. `RingBuffer` is a
+/// cheap handle, and clones share the same buffer.
+///
+/// ```no_run
+/// use serif::{Config, RingBuffer};
+/// use serif::tracing::Level;
+///
+/// let ring = RingBuffer::new(500);
+/// let _guard = Config::new().with_ring_buffer(ring.clone(), Level::TRACE).init();
+/// # fn run() -> Result<(), ()> { Ok(()) }
+/// if run().is_err() {
+///     ring.write_to_file("debug.log").expect("failed to write debug log");
+/// }
+/// ```
+///
+/// `RingBuffer` implements [`MakeWriter`], so it can also be used directly with a
+/// tracing-subscriber [`SubscriberBuilder`] or [`Layer`]. Each event should be written in a single
+/// [`MakeWriter::make_writer`] call, which is how tracing-subscriber writes formatted events.
+///
+/// [`SubscriberBuilder`]: tracing_subscriber::fmt::SubscriberBuilder
+/// [`Layer`]: tracing_subscriber::fmt::Layer
+#[derive(Clone)]
+pub struct RingBuffer {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    capacity: usize,
+    events: Mutex<VecDeque<String>>,
+}
+
+impl RingBuffer {
+    /// Create a ring buffer which holds up to `capacity` events, discarding the oldest events
+    /// when full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "RingBuffer capacity must be non-zero");
+        let events = Mutex::new(VecDeque::with_capacity(capacity));
+        Self { shared: Arc::new(Shared { capacity, events }) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<String>> {
+        // This may be called from a panic hook after a panic while the lock was held, which
+        // doesn't leave the buffer in a bad state.
+        self.shared.events.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// The maximum number of events held.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// The number of events currently held.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Is the buffer empty?
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remove and return all held events, oldest first. Each event is formatted text ending with
+    /// a newline.
+    pub fn drain(&self) -> Vec<String> {
+        self.lock().drain(..).collect()
+    }
+
+    /// Write all held events to `writer`, oldest first. The events aren't removed.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        // copy the events first so that slow writers don't block logging
+        let events: Vec<String> = self.lock().iter().cloned().collect();
+        for event in events {
+            writer.write_all(event.as_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// Write all held events to a file, creating or truncating it. The events aren't removed.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(io::BufWriter::new(File::create(path)?))
+    }
+
+    fn push(&self, event: String) {
+        let mut events = self.lock();
+        if events.len() == self.shared.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+impl fmt::Debug for RingBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RingBuffer {
+    type Writer = RingWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RingWriter { ring: self, buf: Vec::new() }
+    }
+}
+
+/// The writer for a single event, which adds it to the ring buffer when dropped.
+#[doc(hidden)]
+pub struct RingWriter<'a> {
+    ring: &'a RingBuffer,
+    buf: Vec<u8>,
+}
+
+impl Write for RingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RingWriter<'_> {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            self.ring.push(String::from_utf8_lossy(&self.buf).into_owned());
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! `Serialize` and `Deserialize` implementations for serif's configuration types, enabled by the
+//! `serde` feature. See the "Configuration files" section of [`Config`]'s docs.
+//!
+//! Most types are represented as the same strings that their `FromStr` implementations accept,
+//! which keeps configuration files consistent with command line flags and environment variables.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, Serializer};
+use tracing_subscriber::filter::{Directive, LevelFilter};
+
+use crate::config::is_valid_target;
+use crate::{ColorMode, Config, Format, InnerTimeFormat, Output, TimeFormat};
+
+/// Deserialize a string using `FromStr`, with the parse error as the message.
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(de::Error::custom)
+}
+
+impl Serialize for ColorMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+impl Serialize for Format {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::Full => "full",
+            Self::Compact => "compact",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Format {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+/// `Output` is represented as `"stdout"`, `"stderr"`, `"syslog"` or `"journald"` (with the
+/// default [`Syslog`] or [`Journald`] options), or any other string as a file path.
+///
+/// [`Syslog`]: crate::Syslog
+/// [`Journald`]: crate::Journald
+impl Serialize for Output {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Stdout => serializer.serialize_str("stdout"),
+            Self::Stderr => serializer.serialize_str("stderr"),
+            Self::File(path) => match path.to_str() {
+                Some(path) => serializer.serialize_str(path),
+                None => Err(ser::Error::custom("log file path is not valid unicode")),
+            },
+            #[cfg(unix)]
+            Self::Syslog(syslog) if syslog.is_default() => serializer.serialize_str("syslog"),
+            #[cfg(unix)]
+            Self::Syslog(_) => Err(ser::Error::custom("custom syslog options can't be serialized")),
+            #[cfg(unix)]
+            Self::Journald(journald) if journald.is_default() => {
+                serializer.serialize_str("journald")
+            }
+            #[cfg(unix)]
+            Self::Journald(_) => {
+                Err(ser::Error::custom("custom journald options can't be serialized"))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Output {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.is_empty() {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Str(&s),
+                &"'stdout', 'stderr', 'syslog', 'journald', or a file path",
+            ));
+        }
+        Ok(s.parse().unwrap_or_else(|_| Output::File(PathBuf::from(s))))
+    }
+}
+
+/// `TimeFormat` is represented as a string like `"none"`, `"local"`, or `"utc:%H:%M:%S"`, see its
+/// `FromStr` implementation.
+impl Serialize for TimeFormat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.inner {
+            InnerTimeFormat::None => serializer.serialize_str("none"),
+            InnerTimeFormat::Local(None) => serializer.serialize_str("local"),
+            InnerTimeFormat::Utc(None) => serializer.serialize_str("utc"),
+            InnerTimeFormat::Local(Some(format)) => {
+                serializer.collect_str(&format_args!("local:{format}"))
+            }
+            InnerTimeFormat::Utc(Some(format)) => {
+                serializer.collect_str(&format_args!("utc:{format}"))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeFormat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+/// Serialize and deserialize a `Directive` as a string.
+mod directive {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        directive: &Directive,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(directive)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Directive, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+/// Per-target levels, represented as a map of target names to level strings.
+struct TargetLevels(Vec<(String, LevelFilter)>);
+
+impl Serialize for TargetLevels {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (target, level) in &self.0 {
+            map.serialize_entry(target, &format_args!("{level}"))?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TargetLevels {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TargetLevelsVisitor;
+
+        impl<'de> Visitor<'de> for TargetLevelsVisitor {
+            type Value = TargetLevels;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of targets to levels")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut levels = Vec::<(String, LevelFilter)>::new();
+                while let Some(target) = map.next_key::<String>()? {
+                    if !is_valid_target(&target) {
+                        return Err(de::Error::custom(format_args!(
+                            "invalid target for a filter directive: '{target}'"
+                        )));
+                    }
+                    let level = map.next_value::<String>()?;
+                    let level = level.parse::<LevelFilter>().map_err(|_| {
+                        de::Error::custom(format_args!(
+                            "invalid level '{level}' for target '{target}'"
+                        ))
+                    })?;
+                    levels.retain(|(t, _)| *t != target);
+                    levels.push((target, level));
+                }
+                Ok(TargetLevels(levels))
+            }
+        }
+
+        deserializer.deserialize_map(TargetLevelsVisitor)
+    }
+}
+
+/// The serialized form of a `Config`.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigRepr {
+    #[serde(with = "directive")]
+    level: Directive,
+    targets: TargetLevels,
+    color: ColorMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<TimeFormat>,
+    output: Output,
+    format: Format,
+}
+
+impl Default for ConfigRepr {
+    fn default() -> Self {
+        let config = Config::new();
+        Self { timestamp: None, ..ConfigRepr::from(&config) }
+    }
+}
+
+impl From<&Config> for ConfigRepr {
+    fn from(config: &Config) -> Self {
+        Self {
+            level: config.default_directive.clone(),
+            targets: TargetLevels(config.target_levels.clone()),
+            color: config.color,
+            timestamp: Some(config.event_formatter.time_format.clone()),
+            output: config.output.clone(),
+            format: config.format,
+        }
+    }
+}
+
+impl From<ConfigRepr> for Config {
+    fn from(repr: ConfigRepr) -> Self {
+        let mut config = Config::new().with_format(repr.format);
+        if let Some(time_format) = repr.timestamp {
+            config = config.with_timestamp(time_format);
+        }
+        config.target_levels = repr.targets.0;
+        config.with_default(repr.level).with_color(repr.color).with_output(repr.output)
+    }
+}
+
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ConfigRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ConfigRepr::deserialize(deserializer).map(Config::from)
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! End-of-run summaries of warnings and errors, see [`Config::with_summary`]. This module is
+//! private, but its pub items are exported and inlined at the top-level of the `serif` crate.
+//!
+//! [`Config::with_summary`]: crate::Config::with_summary
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tracing_core::{Event, Level, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::suppress::FLUSH_TARGET;
+
+/// The target of the summary event, which isn't counted itself.
+pub(crate) const SUMMARY_TARGET: &str = "serif::summary";
+
+/// The summary state installed by [`Config::init`](crate::Config::init).
+static SUMMARY: OnceLock<Arc<SummaryState>> = OnceLock::new();
+
+/// The number of events logged at each level, returned by [`summary`].
+///
+/// Its `Display` implementation lists the warnings and errors, like `3 warnings, 1 error`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+    counts: [u64; 5],
+}
+
+impl Counts {
+    /// The number of events logged at `level`.
+    pub fn get(&self, level: Level) -> u64 {
+        self.counts[level_index(level)]
+    }
+
+    /// The number of WARN events.
+    pub fn warnings(&self) -> u64 {
+        self.get(Level::WARN)
+    }
+
+    /// The number of ERROR events.
+    pub fn errors(&self) -> u64 {
+        self.get(Level::ERROR)
+    }
+}
+
+impl fmt::Display for Counts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = |n: u64| if n == 1 { "" } else { "s" };
+        let (warnings, errors) = (self.warnings(), self.errors());
+        write!(f, "{warnings} warning{}, {errors} error{}", plural(warnings), plural(errors))
+    }
+}
+
+fn level_index(level: Level) -> usize {
+    match level {
+        Level::TRACE => 0,
+        Level::DEBUG => 1,
+        Level::INFO => 2,
+        Level::WARN => 3,
+        Level::ERROR => 4,
+    }
+}
+
+/// Event counts and recent WARN and ERROR lines, shared between [`SummaryLayer`] and
+/// [`EventFormatter`](crate::EventFormatter).
+#[derive(Debug)]
+pub(crate) struct SummaryState {
+    counts: [AtomicU64; 5],
+    keep: usize,
+    lines: Mutex<VecDeque<String>>,
+    printed: AtomicBool,
+}
+
+impl SummaryState {
+    /// Create the summary state and install it globally for [`summary`].
+    pub(crate) fn install(keep: usize) -> Arc<Self> {
+        let state = Arc::new(Self {
+            counts: Default::default(),
+            keep,
+            lines: Mutex::new(VecDeque::with_capacity(keep)),
+            printed: AtomicBool::new(false),
+        });
+        // Config::init can only succeed once, so this is never already set
+        let _ = SUMMARY.set(state.clone());
+        state
+    }
+
+    /// Should formatted lines at this level be kept?
+    pub(crate) fn keeps(&self, level: Level) -> bool {
+        self.keep > 0 && level <= Level::WARN
+    }
+
+    /// Keep a formatted line, discarding the oldest if there are too many.
+    pub(crate) fn keep_line(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|err| err.into_inner());
+        if lines.len() == self.keep {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// The kept lines, oldest first.
+    pub(crate) fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap_or_else(|err| err.into_inner()).iter().cloned().collect()
+    }
+
+    fn counts(&self) -> Counts {
+        Counts { counts: self.counts.each_ref().map(|count| count.load(Ordering::Relaxed)) }
+    }
+
+    /// Log the summary event, if there were any warnings or errors. Only then is the summary
+    /// considered printed, so that the [`Guard`](crate::Guard) prints any later warnings.
+    fn print(&self) -> Counts {
+        let counts = self.counts();
+        if counts.errors() > 0 {
+            tracing::error!(target: SUMMARY_TARGET, "{counts}");
+        } else if counts.warnings() > 0 {
+            tracing::warn!(target: SUMMARY_TARGET, "{counts}");
+        } else {
+            return counts;
+        }
+        self.printed.store(true, Ordering::Relaxed);
+        counts
+    }
+}
+
+/// Print a summary of warnings and errors, and return the number of events logged at each level.
+///
+/// The summary is logged as a single event like `3 warnings, 1 error`, at the ERROR level if
+/// there were any errors or at the WARN level otherwise, followed by the most recent WARN and
+/// ERROR lines that were kept. Nothing is printed if there weren't any warnings or errors.
+///
+/// Returns `None` without printing anything if summaries weren't enabled with
+/// [`Config::with_summary`](crate::Config::with_summary).
+///
+/// ```no_run
+/// let guard = serif::Config::new().with_summary(5).init();
+/// // run the job
+/// let counts = serif::summary().unwrap();
+/// drop(guard);
+/// std::process::exit(if counts.errors() > 0 { 1 } else { 0 });
+/// ```
+pub fn summary() -> Option<Counts> {
+    SUMMARY.get().map(|state| state.print())
+}
+
+/// Print the summary when the [`Guard`](crate::Guard) is dropped, unless it's already been
+/// printed by [`summary`].
+pub(crate) fn finish() {
+    if let Some(state) = SUMMARY.get() {
+        if !state.printed.load(Ordering::Relaxed) {
+            state.print();
+        }
+    }
+}
+
+/// A [`Layer`] which counts events at each level.
+///
+/// Events are counted in `event_enabled` rather than `on_event`, so that events suppressed by
+/// [`SuppressLayer`](crate::suppress::SuppressLayer) are still counted. This must be the outer
+/// layer for that, because the outer layer's `event_enabled` is called first.
+#[derive(Debug, Clone)]
+pub(crate) struct SummaryLayer(pub(crate) Arc<SummaryState>);
+
+impl<S: Subscriber> Layer<S> for SummaryLayer {
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        let normalized = event.normalized_metadata();
+        let meta = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        if meta.target() != SUMMARY_TARGET && meta.target() != FLUSH_TARGET {
+            self.0.counts[level_index(*meta.level())].fetch_add(1, Ordering::Relaxed);
+        }
+        true
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Suppression of duplicate and excessive events, see [`Config::with_dedup`] and
+//! [`Config::with_rate_limit`].
+//!
+//! [`Config::with_dedup`]: crate::Config::with_dedup
+//! [`Config::with_rate_limit`]: crate::Config::with_rate_limit
+
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing_core::callsite::Identifier;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::layer::{Context, Layer};
+
+/// The target of the event logged by [`Suppressor::flush`], which only writes pending summaries.
+pub(crate) const FLUSH_TARGET: &str = "serif::suppress";
+
+/// Identifies where an event came from. Events from the `log` crate share a callsite for each
+/// level, so their normalized target and line are included too.
+type SiteKey = (Identifier, String, Option<u32>);
+
+/// A summary of suppressed events, which is written before the next event that's logged.
+#[derive(Debug)]
+pub(crate) struct Summary {
+    pub(crate) level: Level,
+    pub(crate) target: String,
+    pub(crate) text: String,
+}
+
+/// The suppression state of a single callsite.
+#[derive(Debug)]
+struct Site {
+    level: Level,
+    message: String,
+    /// When the current message was last logged, the start of the dedup window.
+    logged_at: Instant,
+    repeated: u64,
+    tokens: f64,
+    refilled_at: Instant,
+    dropped: u64,
+}
+
+impl Site {
+    /// Refill the token bucket for the time elapsed since the last refill.
+    fn refill(&mut self, now: Instant, (burst, interval): (u32, Duration)) {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        let rate = f64::from(burst) / interval.as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(f64::from(burst));
+        self.refilled_at = now;
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    sites: HashMap<SiteKey, Site>,
+    summaries: Vec<Summary>,
+    /// When all sites were last checked for summaries.
+    swept_at: Option<Instant>,
+}
+
+/// Shared state for suppressing events, used by [`SuppressLayer`] to filter events and by
+/// [`EventFormatter`](crate::EventFormatter) to write summaries.
+#[derive(Debug)]
+pub(crate) struct Suppressor {
+    dedup_window: Option<Duration>,
+    rate_limit: Option<(u32, Duration)>,
+    /// How often to check all sites for summaries: the shortest time for a dedup window to end
+    /// or for a rate limit to refill one token.
+    sweep_interval: Duration,
+    state: Mutex<State>,
+}
+
+impl Suppressor {
+    pub(crate) fn new(
+        dedup_window: Option<Duration>,
+        rate_limit: Option<(u32, Duration)>,
+    ) -> Arc<Self> {
+        let refill = rate_limit.map(|(burst, interval)| interval / burst);
+        let sweep_interval = dedup_window.into_iter().chain(refill).min().unwrap_or(Duration::MAX);
+        Arc::new(Self { dedup_window, rate_limit, sweep_interval, state: Default::default() })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // a panic while holding the lock doesn't leave the state inconsistent
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Take the summaries which are ready to be written.
+    pub(crate) fn take_summaries(&self) -> Vec<Summary> {
+        mem::take(&mut self.lock().summaries)
+    }
+
+    /// Summarize every callsite with suppressed events and write the summaries, so that nothing
+    /// is lost at exit. This logs an event which [`EventFormatter`](crate::EventFormatter) only
+    /// writes the pending summaries for.
+    pub(crate) fn flush(&self) {
+        {
+            let mut state = self.lock();
+            let State { sites, summaries, .. } = &mut *state;
+            for ((_, target, _), site) in sites.iter_mut() {
+                if site.repeated > 0 {
+                    summaries.push(repeated_summary(target, site));
+                }
+                if site.dropped > 0 {
+                    summaries.push(dropped_summary(target, site));
+                }
+            }
+            if summaries.is_empty() {
+                return;
+            }
+        }
+        tracing::error!(target: FLUSH_TARGET, "");
+    }
+
+    /// Decide whether an event should be logged, updating its callsite's state.
+    fn check(&self, event: &Event<'_>) -> bool {
+        let normalized = event.normalized_metadata();
+        let meta = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        if meta.target() == FLUSH_TARGET {
+            return true;
+        }
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        let message = visitor.0;
+
+        let now = Instant::now();
+        let key = (event.metadata().callsite(), meta.target().to_owned(), meta.line());
+        let mut state = self.lock();
+        let State { sites, summaries, swept_at } = &mut *state;
+        if swept_at.is_none_or(|at| now.duration_since(at) >= self.sweep_interval) {
+            self.sweep(sites, summaries, now);
+            *swept_at = Some(now);
+        }
+
+        let Some(site) = sites.get_mut(&key) else {
+            let tokens = self.rate_limit.map_or(0.0, |(burst, _)| f64::from(burst) - 1.0);
+            let site = Site {
+                level: *meta.level(),
+                message,
+                logged_at: now,
+                repeated: 0,
+                tokens,
+                refilled_at: now,
+                dropped: 0,
+            };
+            sites.insert(key, site);
+            return true;
+        };
+
+        if let Some(window) = self.dedup_window {
+            if site.message == message && now.duration_since(site.logged_at) < window {
+                site.repeated += 1;
+                return false;
+            }
+            if site.repeated > 0 {
+                summaries.push(repeated_summary(&key.1, site));
+            }
+        }
+
+        if let Some(rate_limit) = self.rate_limit {
+            site.refill(now, rate_limit);
+            if site.tokens < 1.0 {
+                site.dropped += 1;
+                return false;
+            }
+            site.tokens -= 1.0;
+            if site.dropped > 0 {
+                summaries.push(dropped_summary(&key.1, site));
+            }
+        }
+
+        site.message = message;
+        site.logged_at = now;
+        true
+    }
+
+    /// Summarize callsites whose dedup window has ended or whose rate limit has recovered.
+    fn sweep(
+        &self,
+        sites: &mut HashMap<SiteKey, Site>,
+        summaries: &mut Vec<Summary>,
+        now: Instant,
+    ) {
+        for ((_, target, _), site) in sites.iter_mut() {
+            if let Some(window) = self.dedup_window {
+                if site.repeated > 0 && now.duration_since(site.logged_at) >= window {
+                    summaries.push(repeated_summary(target, site));
+                }
+            }
+            if let Some(rate_limit) = self.rate_limit {
+                if site.dropped > 0 {
+                    site.refill(now, rate_limit);
+                    if site.tokens >= 1.0 {
+                        summaries.push(dropped_summary(target, site));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Summarize repeated events, resetting the count.
+fn repeated_summary(target: &str, site: &mut Site) -> Summary {
+    let count = mem::take(&mut site.repeated);
+    let text = format!("(repeated {count} time{})", if count == 1 { "" } else { "s" });
+    Summary { level: site.level, target: target.to_owned(), text }
+}
+
+/// Summarize rate-limited events, resetting the count.
+fn dropped_summary(target: &str, site: &mut Site) -> Summary {
+    let count = mem::take(&mut site.dropped);
+    let text =
+        format!("({count} event{} dropped by rate limit)", if count == 1 { "" } else { "s" });
+    Summary { level: site.level, target: target.to_owned(), text }
+}
+
+/// Records an event's message.
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        }
+    }
+}
+
+/// A [`Layer`] which disables events that are suppressed by a [`Suppressor`].
+#[derive(Debug, Clone)]
+pub(crate) struct SuppressLayer(pub(crate) Arc<Suppressor>);
+
+impl<S: Subscriber> Layer<S> for SuppressLayer {
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        self.0.check(event)
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Output to a local syslog daemon over a Unix datagram socket. This module is private, but its
+//! pub types are exported and inlined at the top-level of the `serif` crate.
+
+use std::io::{self, Write};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::{env, fmt, process};
+
+use jiff::{Timestamp, Zoned};
+use tracing_core::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::severity;
+
+/// A syslog facility, which tells the syslog daemon what kind of program is logging.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Facility {
+    Kern = 0,
+    /// Generic user-level messages. This is the default.
+    #[default]
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    AuthPriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// The format of syslog messages.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SyslogFormat {
+    /// [RFC 5424](https://www.rfc-editor.org/rfc/rfc5424) messages, with a UTC timestamp. This
+    /// is the default.
+    #[default]
+    Rfc5424,
+    /// [RFC 3164](https://www.rfc-editor.org/rfc/rfc3164) (BSD syslog) messages, with a local
+    /// timestamp and no hostname, like glibc's `syslog(3)` sends.
+    Rfc3164,
+}
+
+/// Options for logging to syslog with [`Output::Syslog`](crate::Output::Syslog).
+///
+/// Each event is formatted by [`EventFormatter`](crate::EventFormatter) as usual, then sent to the
+/// syslog daemon as one message, with a header containing the facility, severity, timestamp, app
+/// name, and process ID. Since syslog adds its own timestamps, consider using
+/// [`TimeFormat::none`](crate::TimeFormat::none) for the message itself.
+///
+/// tracing levels are mapped to syslog severities: ERROR is `err` (3), WARN is `warning` (4),
+/// INFO is `info` (6), and DEBUG and TRACE are `debug` (7).
+#[derive(Debug, Clone)]
+pub struct Syslog {
+    path: PathBuf,
+    facility: Facility,
+    format: SyslogFormat,
+    app_name: Option<String>,
+}
+
+impl Default for Syslog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Syslog {
+    /// The default socket path of the local syslog daemon.
+    pub const DEFAULT_PATH: &'static str = "/dev/log";
+
+    /// Create the default syslog options, using [`DEFAULT_PATH`](Syslog::DEFAULT_PATH), the
+    /// `user` facility, and RFC 5424 formatting.
+    pub fn new() -> Self {
+        Self {
+            path: PathBuf::from(Self::DEFAULT_PATH),
+            facility: Facility::default(),
+            format: SyslogFormat::default(),
+            app_name: None,
+        }
+    }
+
+    /// Set the path of the syslog daemon's socket.
+    pub fn with_path(self, path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), ..self }
+    }
+
+    /// Set the syslog facility. The default is [`Facility::User`].
+    pub fn with_facility(self, facility: Facility) -> Self {
+        Self { facility, ..self }
+    }
+
+    /// Set the message format. The default is [`SyslogFormat::Rfc5424`].
+    pub fn with_format(self, format: SyslogFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Set the app name (or "tag") sent with each message. The default is the file name of the
+    /// current executable.
+    pub fn with_app_name(self, app_name: impl Into<String>) -> Self {
+        Self { app_name: Some(app_name.into()), ..self }
+    }
+
+    /// The path of the syslog daemon's socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Is this the default configuration?
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn is_default(&self) -> bool {
+        self.path == Path::new(Self::DEFAULT_PATH)
+            && self.facility == Facility::default()
+            && self.format == SyslogFormat::default()
+            && self.app_name.is_none()
+    }
+
+    /// Connect to the syslog daemon's socket, returning a [`MakeWriter`] which can be used
+    /// directly with a [`SubscriberBuilder`].
+    ///
+    /// [`SubscriberBuilder`]: tracing_subscriber::fmt::SubscriberBuilder
+    pub fn connect(&self) -> io::Result<SyslogWriter> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&self.path)?;
+
+        let app_name = self.app_name.clone().unwrap_or_else(default_app_name);
+        // RFC 5424 limits APP-NAME to 48 printable ASCII characters, without spaces.
+        let app_name =
+            app_name.chars().map(|c| if c.is_ascii_graphic() { c } else { '_' }).take(48).collect();
+
+        Ok(SyslogWriter {
+            socket,
+            path: self.path.clone(),
+            facility: self.facility,
+            format: self.format,
+            app_name,
+            pid: process::id(),
+        })
+    }
+}
+
+/// The file name of the current executable, or `-` if it's unknown.
+pub(crate) fn default_app_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// A [`MakeWriter`] which sends each event to syslog, created by [`Syslog::connect`].
+pub struct SyslogWriter {
+    socket: UnixDatagram,
+    path: PathBuf,
+    facility: Facility,
+    format: SyslogFormat,
+    app_name: String,
+    pid: u32,
+}
+
+impl fmt::Debug for SyslogWriter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SyslogWriter")
+            .field("path", &self.path)
+            .field("facility", &self.facility)
+            .field("format", &self.format)
+            .field("app_name", &self.app_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SyslogWriter {
+    /// Build a complete syslog message for a formatted event.
+    fn message(&self, severity: u8, msg: &[u8]) -> Vec<u8> {
+        let pri = self.facility as u8 * 8 + severity;
+        let msg = msg.strip_suffix(b"\n").unwrap_or(msg);
+        let mut buf = Vec::with_capacity(msg.len() + 64);
+        // Writing to a Vec can't fail
+        let _ = match self.format {
+            SyslogFormat::Rfc5424 => write!(
+                buf,
+                "<{pri}>1 {} - {} {} - - ",
+                Timestamp::now().strftime("%Y-%m-%dT%H:%M:%S%.6fZ"),
+                self.app_name,
+                self.pid,
+            ),
+            SyslogFormat::Rfc3164 => write!(
+                buf,
+                "<{pri}>{} {}[{}]: ",
+                Zoned::now().strftime("%b %e %H:%M:%S"),
+                self.app_name,
+                self.pid,
+            ),
+        };
+        buf.extend_from_slice(msg);
+        buf
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        match self.socket.send(message) {
+            Ok(_) => Ok(()),
+            Err(_) => {
+                // The syslog daemon may have restarted, so reconnect and try once more.
+                self.socket.connect(&self.path)?;
+                self.socket.send(message).map(|_| ())
+            }
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogWriter {
+    type Writer = SyslogEvent<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogEvent { writer: self, severity: severity(&Level::INFO), buf: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        SyslogEvent { writer: self, severity: severity(meta.level()), buf: Vec::new() }
+    }
+}
+
+/// The writer for a single event, which sends it to syslog when dropped.
+#[doc(hidden)]
+pub struct SyslogEvent<'a> {
+    writer: &'a SyslogWriter,
+    severity: u8,
+    buf: Vec<u8>,
+}
+
+impl Write for SyslogEvent<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SyslogEvent<'_> {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            // Errors are ignored, the same as tracing-subscriber does when writing directly.
+            let _ = self.writer.send(&self.writer.message(self.severity, &self.buf));
+        }
+    }
+}
@@ -0,0 +1,428 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Helpers for testing that code logs the right things, enabled by the `testing` feature.
+//!
+//! [`capture`] installs a thread-local subscriber which formats events using [`EventFormatter`]
+//! and [`FieldFormatter`], just like [`Config::init`](crate::Config::init) would, and saves both
+//! the formatted lines and the structured events so that tests can make assertions about them.
+//! Formatted lines are also printed in a way that `cargo test`'s output capture picks up, so
+//! they're shown for failing tests.
+//!
+//! By default, colors and timestamps are disabled and all levels are captured.
+//!
+//! ```
+//! use serif::macros::*;
+//! use serif::testing::{self, EventMatcher};
+//! use serif::tracing::Level;
+//!
+//! let capture = testing::capture();
+//! warn!(target: "app", user = "bob", "login failed");
+//!
+//! assert_eq!(capture.lines(), [" WARN app: login failed [user=\"bob\"]"]);
+//! capture.assert_logged(
+//!     &EventMatcher::new().with_level(Level::WARN).with_message("failed").with_field("user", "bob"),
+//! );
+//! ```
+
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tracing::subscriber::DefaultGuard;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::filter::{Directive, EnvFilter, LevelFilter};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+use crate::{Config, EventFormatter, FieldFormatter, TimeFormat, is_sub_target};
+
+/// Start capturing events on the current thread with the default options.
+///
+/// This is shorthand for `CaptureBuilder::new().start()`.
+pub fn capture() -> Capture {
+    CaptureBuilder::new().start()
+}
+
+/// Builder for a [`Capture`].
+#[derive(Debug, Clone)]
+pub struct CaptureBuilder {
+    event_formatter: EventFormatter,
+    field_formatter: FieldFormatter,
+    ansi: bool,
+    echo: bool,
+    default_directive: Directive,
+}
+
+impl Default for CaptureBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaptureBuilder {
+    /// Create a new `CaptureBuilder` with the default options.
+    ///
+    /// The default `EventFormatter` has timestamps disabled, so that formatted lines are
+    /// predictable.
+    pub fn new() -> Self {
+        Self {
+            event_formatter: EventFormatter::new().with_timestamp(TimeFormat::none()),
+            field_formatter: FieldFormatter::new(),
+            ansi: false,
+            echo: true,
+            default_directive: LevelFilter::TRACE.into(),
+        }
+    }
+
+    /// Set the event formatter used to format lines.
+    pub fn with_event_formatter(self, event_formatter: EventFormatter) -> Self {
+        Self { event_formatter, ..self }
+    }
+
+    /// Set the field formatter used to format lines.
+    pub fn with_field_formatter(self, field_formatter: FieldFormatter) -> Self {
+        Self { field_formatter, ..self }
+    }
+
+    /// Format lines using the event and field formatters of a [`Config`], including its timestamp
+    /// format and [`Clock`](crate::Clock).
+    ///
+    /// The [`Profile`] is applied for the config's output like [`Config::init`] does, detecting it
+    /// now for [`Profile::Auto`]. Wrapping to the terminal width and colors aren't applied, use
+    /// [`with_ansi`](CaptureBuilder::with_ansi) for colors.
+    ///
+    /// [`Profile`]: crate::Profile
+    /// [`Profile::Auto`]: crate::Profile::Auto
+    pub fn with_config(self, config: &Config) -> Self {
+        Self {
+            event_formatter: config.output_event_formatter(),
+            field_formatter: config.field_formatter.clone(),
+            ..self
+        }
+    }
+
+    /// Enable or disable ANSI colors in formatted lines. The default is `false`.
+    pub fn with_ansi(self, ansi: bool) -> Self {
+        Self { ansi, ..self }
+    }
+
+    /// Enable or disable printing formatted lines to the test's output, which `cargo test`
+    /// captures and shows only for failing tests. The default is `true`.
+    pub fn with_echo(self, echo: bool) -> Self {
+        Self { echo, ..self }
+    }
+
+    /// Set the filter directive for captured events. The default is the TRACE level, which
+    /// captures everything. `RUST_LOG` is not used.
+    pub fn with_default(self, default: impl Into<Directive>) -> Self {
+        Self { default_directive: default.into(), ..self }
+    }
+
+    /// Install the capturing subscriber as the default for the current thread.
+    ///
+    /// Capturing stops when the returned [`Capture`] is dropped.
+    pub fn start(self) -> Capture {
+        let shared = Arc::new(Mutex::new(Captured::default()));
+        let fmt_layer = tracing_subscriber::fmt::layer()
+            .with_ansi(self.ansi)
+            .event_format(self.event_formatter)
+            .fmt_fields(self.field_formatter)
+            .with_writer(CaptureWriter { shared: shared.clone(), echo: self.echo });
+        let subscriber = tracing_subscriber::registry()
+            .with(EnvFilter::default().add_directive(self.default_directive))
+            .with(fmt_layer)
+            .with(RecordLayer { shared: shared.clone() });
+
+        let guard = tracing::subscriber::set_default(subscriber);
+        Capture { shared, _guard: guard }
+    }
+}
+
+/// Everything that's been captured so far.
+#[derive(Debug, Default)]
+struct Captured {
+    /// Formatted output of the current event, which is moved into an event by `RecordLayer`.
+    output: String,
+    /// Structured events, in the order they were emitted.
+    events: Vec<CapturedEvent>,
+}
+
+/// A thread-local capturing subscriber, created by [`capture`] or [`CaptureBuilder::start`].
+///
+/// The subscriber is uninstalled when this is dropped.
+pub struct Capture {
+    shared: Arc<Mutex<Captured>>,
+    _guard: DefaultGuard,
+}
+
+impl fmt::Debug for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Capture").field("events", &self.lock().events).finish_non_exhaustive()
+    }
+}
+
+impl Capture {
+    fn lock(&self) -> MutexGuard<'_, Captured> {
+        // a panic while holding the lock doesn't leave the data in a bad state
+        self.shared.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Get all the formatted lines captured so far, without trailing newlines.
+    pub fn lines(&self) -> Vec<String> {
+        let captured = self.lock();
+        captured.events.iter().flat_map(|e| e.output.lines()).map(String::from).collect()
+    }
+
+    /// Get all the events captured so far.
+    pub fn events(&self) -> Vec<CapturedEvent> {
+        self.lock().events.clone()
+    }
+
+    /// Discard everything captured so far.
+    pub fn clear(&self) {
+        self.lock().events.clear();
+    }
+
+    /// Count the captured events which match `matcher`.
+    pub fn count(&self, matcher: &EventMatcher) -> usize {
+        self.lock().events.iter().filter(|e| matcher.matches(e)).count()
+    }
+
+    /// Get the first captured event which matches `matcher`, if any.
+    pub fn find(&self, matcher: &EventMatcher) -> Option<CapturedEvent> {
+        self.lock().events.iter().find(|e| matcher.matches(e)).cloned()
+    }
+
+    /// Assert that at least one captured event matches `matcher`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a list of captured lines if no events match.
+    #[track_caller]
+    pub fn assert_logged(&self, matcher: &EventMatcher) {
+        if self.count(matcher) == 0 {
+            panic!("no event matching {matcher:?} was logged{}", self.dump());
+        }
+    }
+
+    /// Assert that no captured events match `matcher`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a list of captured lines if any events match.
+    #[track_caller]
+    pub fn assert_not_logged(&self, matcher: &EventMatcher) {
+        if self.count(matcher) != 0 {
+            panic!("an event matching {matcher:?} was logged{}", self.dump());
+        }
+    }
+
+    /// Format the captured lines for an assertion message.
+    fn dump(&self) -> String {
+        let lines = self.lines();
+        if lines.is_empty() {
+            return ", nothing was captured".into();
+        }
+        let mut out = String::from(", captured lines:");
+        for line in lines {
+            out.push_str("\n    ");
+            out.push_str(&line);
+        }
+        out
+    }
+}
+
+/// An event saved by [`Capture`].
+#[derive(Debug, Clone)]
+pub struct CapturedEvent {
+    level: Level,
+    target: String,
+    message: String,
+    fields: Vec<(String, String)>,
+    output: String,
+}
+
+impl CapturedEvent {
+    /// The event's level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The event's target. For events from the `log` crate, this is the record's target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The event's message, or an empty string if it has none.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the value of a field other than the message.
+    ///
+    /// String values are returned as-is, errors are formatted using `Display`, and all other
+    /// values are formatted using `Debug`.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// All the fields other than the message, in the order they were recorded.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    /// The event's formatted output, without the trailing newline.
+    pub fn output(&self) -> &str {
+        self.output.strip_suffix('\n').unwrap_or(&self.output)
+    }
+}
+
+/// A set of conditions to check against captured events, used with [`Capture::assert_logged`] and
+/// friends. An event matches if it satisfies every condition that's been set.
+#[derive(Debug, Clone, Default)]
+pub struct EventMatcher {
+    level: Option<Level>,
+    target: Option<String>,
+    message: Option<String>,
+    fields: Vec<(String, String)>,
+}
+
+impl EventMatcher {
+    /// Create a new `EventMatcher` which matches any event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match events with exactly this level.
+    pub fn with_level(self, level: Level) -> Self {
+        Self { level: Some(level), ..self }
+    }
+
+    /// Match events whose target is `target` or nested under it, like `target::child`.
+    pub fn with_target(self, target: impl Into<String>) -> Self {
+        Self { target: Some(target.into()), ..self }
+    }
+
+    /// Match events whose message contains `message`.
+    pub fn with_message(self, message: impl Into<String>) -> Self {
+        Self { message: Some(message.into()), ..self }
+    }
+
+    /// Match events which have a field with this name and value, see [`CapturedEvent::field`]
+    /// for how values are formatted. This can be called multiple times to match multiple fields.
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    /// Check whether an event matches.
+    pub fn matches(&self, event: &CapturedEvent) -> bool {
+        self.level.is_none_or(|level| event.level == level)
+            && self.target.as_ref().is_none_or(|t| is_sub_target(&event.target, t))
+            && self.message.as_ref().is_none_or(|m| event.message.contains(m.as_str()))
+            && self.fields.iter().all(|(name, value)| event.field(name) == Some(value.as_str()))
+    }
+}
+
+/// MakeWriter for the formatting layer, which saves output for [`RecordLayer`] to pick up.
+struct CaptureWriter {
+    shared: Arc<Mutex<Captured>>,
+    echo: bool,
+}
+
+impl<'a> MakeWriter<'a> for CaptureWriter {
+    type Writer = CaptureEventWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        CaptureEventWriter { writer: self, buf: Vec::new() }
+    }
+}
+
+/// Writer for a single event's output, which is saved when dropped.
+struct CaptureEventWriter<'a> {
+    writer: &'a CaptureWriter,
+    buf: Vec<u8>,
+}
+
+impl io::Write for CaptureEventWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for CaptureEventWriter<'_> {
+    fn drop(&mut self) {
+        let output = String::from_utf8_lossy(&self.buf);
+        if self.writer.echo {
+            // print! rather than writing to stdout directly, so that libtest captures it
+            print!("{output}");
+        }
+        let mut shared = self.writer.shared.lock().unwrap_or_else(|err| err.into_inner());
+        shared.output.push_str(&output);
+    }
+}
+
+/// Layer which saves structured events, along with the output from the formatting layer. This
+/// must be layered on top of the formatting layer, so that it sees each event after it's been
+/// formatted.
+struct RecordLayer {
+    shared: Arc<Mutex<Captured>>,
+}
+
+impl<S: Subscriber> Layer<S> for RecordLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let norm_meta = event.normalized_metadata();
+        let meta = norm_meta.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+
+        let mut shared = self.shared.lock().unwrap_or_else(|err| err.into_inner());
+        let output = std::mem::take(&mut shared.output);
+        shared.events.push(CapturedEvent {
+            level: *meta.level(),
+            target: meta.target().to_owned(),
+            message: visitor.message,
+            fields: visitor.fields,
+            output,
+        });
+    }
+}
+
+/// Visitor which collects an event's message and fields as strings.
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl RecordVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        match field.name() {
+            "message" => self.message = value,
+            name if name.starts_with("log.") => (),
+            name => self.fields.push((name.to_owned(), value)),
+        }
+    }
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_owned());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{value:?}"));
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright 2022-2025 Allen Wild
+// SPDX-License-Identifier: Apache-2.0
+//! Writers for log output, and the [`Guard`] which flushes them. This module is private, but its
+//! pub types are exported and inlined at the top-level of the `serif` crate.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak};
+use std::thread;
+use std::time::Duration;
+
+use tracing_core::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::suppress::Suppressor;
+
+/// What a [`NonBlocking`] writer does with a new event when its queue is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the writer thread to make room in the queue. No events are lost, but logging can
+    /// still block if the output is slow. This is the default.
+    #[default]
+    Block,
+    /// Discard the new event.
+    DropNewest,
+    /// Discard the oldest event in the queue to make room for the new event.
+    DropOldest,
+}
+
+/// Queue state shared between [`NonBlocking`] writers and the writer thread.
+struct Shared {
+    state: Mutex<State>,
+    /// Signaled when an event is pushed to the queue.
+    pushed: Condvar,
+    /// Signaled when the writer thread has written events and made room in the queue.
+    written: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+}
+
+struct State {
+    queue: VecDeque<Vec<u8>>,
+    /// Whether the writer thread is currently writing events which it took from the queue.
+    writing: bool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The lock is never held while writing or formatting, so it can't be poisoned by a panic
+        // anywhere outside of this module.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, event: Vec<u8>) {
+        let mut state = self.lock();
+        if state.queue.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::Block => {
+                    while state.queue.len() >= self.capacity {
+                        state = self.written.wait(state).unwrap_or_else(PoisonError::into_inner);
+                    }
+                }
+                OverflowPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    state.queue.pop_front();
+                }
+            }
+        }
+        state.queue.push_back(event);
+        self.pushed.notify_one();
+    }
+
+    /// Wait until the writer thread has written every queued event.
+    fn flush(&self) {
+        let mut state = self.lock();
+        while !state.queue.is_empty() || state.writing {
+            state = self.written.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// The main loop of the writer thread, which runs for the rest of the process.
+    fn run(&self, mut output: Box<dyn Write + Send>) {
+        let mut batch = VecDeque::new();
+        loop {
+            {
+                let mut state = self.lock();
+                while state.queue.is_empty() {
+                    state = self.pushed.wait(state).unwrap_or_else(PoisonError::into_inner);
+                }
+                mem::swap(&mut batch, &mut state.queue);
+                state.writing = true;
+            }
+            // Room was made in the queue, so wake up any blocked loggers before writing.
+            self.written.notify_all();
+
+            // Errors are ignored, the same as tracing-subscriber does when writing directly.
+            for event in batch.drain(..) {
+                let _ = output.write_all(&event);
+            }
+            let _ = output.flush();
+
+            self.lock().writing = false;
+            self.written.notify_all();
+        }
+    }
+}
+
+/// A [`MakeWriter`] which sends formatted events to a background thread to be written, so that
+/// logging doesn't block on slow output.
+///
+/// Events are held in a bounded queue, and [`OverflowPolicy`] controls what happens when it's full.
+/// [`Config::with_non_blocking`](crate::Config::with_non_blocking) sets this up automatically,
+/// but it can also be used directly with a [`SubscriberBuilder`].
+///
+/// The writer thread runs for the rest of the process, so events still in the queue when the
+/// process exits are lost unless the [`Guard`] is held until then.
+///
+/// [`SubscriberBuilder`]: tracing_subscriber::fmt::SubscriberBuilder
+#[derive(Clone)]
+pub struct NonBlocking {
+    shared: Arc<Shared>,
+}
+
+impl NonBlocking {
+    /// Spawn a writer thread which writes to `output`, with a queue of up to `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero, or if the writer thread can't be spawned.
+    pub fn new(
+        output: impl Write + Send + 'static,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> (Self, Guard) {
+        assert!(capacity > 0, "NonBlocking queue capacity must be non-zero");
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State { queue: VecDeque::with_capacity(capacity), writing: false }),
+            pushed: Condvar::new(),
+            written: Condvar::new(),
+            capacity,
+            policy,
+            dropped: AtomicU64::new(0),
+        });
+
+        let output: Box<dyn Write + Send> = Box::new(output);
+        let thread_shared = Arc::clone(&shared);
+        thread::Builder::new()
+            .name(String::from("serif-writer"))
+            .spawn(move || thread_shared.run(output))
+            .expect("Unable to spawn the serif-writer thread");
+
+        let guard = Guard {
+            inner: GuardInner::NonBlocking(Arc::clone(&shared)),
+            suppressor: None,
+            summary: false,
+        };
+        (Self { shared }, guard)
+    }
+
+    /// The number of events which have been discarded because the queue was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for NonBlocking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NonBlocking")
+            .field("capacity", &self.shared.capacity)
+            .field("policy", &self.shared.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> MakeWriter<'a> for NonBlocking {
+    type Writer = NonBlockingWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        NonBlockingWriter { buf: Vec::new(), shared: Arc::clone(&self.shared) }
+    }
+}
+
+/// The writer for a single event, which sends it to the queue when dropped.
+#[doc(hidden)]
+pub struct NonBlockingWriter {
+    buf: Vec<u8>,
+    shared: Arc<Shared>,
+}
+
+impl Write for NonBlockingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for NonBlockingWriter {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            self.shared.push(mem::take(&mut self.buf));
+        }
+    }
+}
+
+/// Shared state for [`Buffered`] writers and the flush thread.
+struct BufferedShared {
+    output: Mutex<BufWriter<Box<dyn Write + Send>>>,
+}
+
+impl BufferedShared {
+    fn lock(&self) -> MutexGuard<'_, BufWriter<Box<dyn Write + Send>>> {
+        self.output.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn flush(&self) {
+        let _ = self.lock().flush();
+    }
+
+    /// Flush periodically until every `Buffered` writer has been dropped.
+    fn run_flusher(shared: Weak<Self>, interval: Duration) {
+        loop {
+            thread::sleep(interval);
+            match shared.upgrade() {
+                Some(shared) => shared.flush(),
+                None => break,
+            }
+        }
+    }
+}
+
+/// A [`MakeWriter`] which buffers output, to avoid the overhead of writing each event separately.
+///
+/// Buffered output is flushed when the buffer fills up, periodically from a background thread,
+/// and immediately after every WARN and ERROR event so that messages leading up to a crash aren't
+/// lost. [`Config::with_buffered`](crate::Config::with_buffered) sets this up automatically, but
+/// it can also be used directly with a [`SubscriberBuilder`].
+///
+/// INFO and more verbose events which are still buffered when the process exits are lost unless
+/// the [`Guard`] is held until then.
+///
+/// [`SubscriberBuilder`]: tracing_subscriber::fmt::SubscriberBuilder
+#[derive(Clone)]
+pub struct Buffered {
+    shared: Arc<BufferedShared>,
+}
+
+impl Buffered {
+    /// Buffer writes to `output`, flushing at least once every `flush_interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `flush_interval` is zero, or if the flush thread can't be spawned.
+    pub fn new(output: impl Write + Send + 'static, flush_interval: Duration) -> (Self, Guard) {
+        assert!(!flush_interval.is_zero(), "Buffered flush interval must be non-zero");
+        let output: Box<dyn Write + Send> = Box::new(output);
+        let shared = Arc::new(BufferedShared { output: Mutex::new(BufWriter::new(output)) });
+
+        let weak = Arc::downgrade(&shared);
+        thread::Builder::new()
+            .name(String::from("serif-flush"))
+            .spawn(move || BufferedShared::run_flusher(weak, flush_interval))
+            .expect("Unable to spawn the serif-flush thread");
+
+        let guard = Guard {
+            inner: GuardInner::Buffered(Arc::clone(&shared)),
+            suppressor: None,
+            summary: false,
+        };
+        (Self { shared }, guard)
+    }
+}
+
+impl fmt::Debug for Buffered {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Buffered").finish_non_exhaustive()
+    }
+}
+
+impl<'a> MakeWriter<'a> for Buffered {
+    type Writer = BufferedWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        BufferedWriter { output: self.shared.lock(), flush: false }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        BufferedWriter { output: self.shared.lock(), flush: *meta.level() <= Level::WARN }
+    }
+}
+
+/// The writer for a single event, which flushes WARN and ERROR events when dropped.
+#[doc(hidden)]
+pub struct BufferedWriter<'a> {
+    output: MutexGuard<'a, BufWriter<Box<dyn Write + Send>>>,
+    flush: bool,
+}
+
+impl Write for BufferedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.output.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+impl Drop for BufferedWriter<'_> {
+    fn drop(&mut self) {
+        if self.flush {
+            let _ = self.output.flush();
+        }
+    }
+}
+
+/// Flushes log output when dropped. Returned by [`Config::init`](crate::Config::init).
+///
+/// Hold this in `main` so that logs aren't lost when the process exits:
+///
+/// ```no_run
+/// let _guard = serif::Config::new().init();
+/// ```
+///
+/// Dropping the guard flushes output, but doesn't stop logging. Dropping it right away (for
+/// example with `let _ = ...` or by not binding it at all) means that output buffered with
+/// [`Config::with_buffered`](crate::Config::with_buffered) or
+/// [`Config::with_non_blocking`](crate::Config::with_non_blocking) can be lost at exit.
+///
+/// Dropping the guard also writes the summaries of events still held back by
+/// [`Config::with_dedup`](crate::Config::with_dedup) or
+/// [`Config::with_rate_limit`](crate::Config::with_rate_limit). If
+/// [`Config::with_summary`](crate::Config::with_summary) is enabled, it also prints the summary of
+/// warnings and errors, unless [`summary`](crate::summary) already did.
+#[derive(Default)]
+#[must_use = "dropping the guard flushes output immediately, hold it until the end of `main`"]
+pub struct Guard {
+    inner: GuardInner,
+    /// Write summaries of suppressed events when dropped.
+    suppressor: Option<Arc<Suppressor>>,
+    /// Print the summary when dropped.
+    summary: bool,
+}
+
+#[derive(Default)]
+enum GuardInner {
+    /// Output is written directly, there's nothing to flush.
+    #[default]
+    Direct,
+    NonBlocking(Arc<Shared>),
+    Buffered(Arc<BufferedShared>),
+}
+
+impl Guard {
+    /// Write summaries of events suppressed by `suppressor` when this guard is dropped.
+    pub(crate) fn with_suppressor(mut self, suppressor: Arc<Suppressor>) -> Self {
+        self.suppressor = Some(suppressor);
+        self
+    }
+
+    /// Print the end-of-run summary when this guard is dropped.
+    pub(crate) fn with_summary(mut self) -> Self {
+        self.summary = true;
+        self
+    }
+
+    /// Wait until all pending log output has been written.
+    pub fn flush(&self) {
+        match &self.inner {
+            GuardInner::Direct => (),
+            GuardInner::NonBlocking(shared) => shared.flush(),
+            GuardInner::Buffered(shared) => shared.flush(),
+        }
+    }
+
+    /// The number of events which have been discarded because a [`NonBlocking`] queue was full.
+    pub fn dropped_events(&self) -> u64 {
+        match &self.inner {
+            GuardInner::NonBlocking(shared) => shared.dropped.load(Ordering::Relaxed),
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match &self.inner {
+            GuardInner::Direct => "Direct",
+            GuardInner::NonBlocking(_) => "NonBlocking",
+            GuardInner::Buffered(_) => "Buffered",
+        };
+        f.debug_tuple("Guard").field(&format_args!("{kind}")).finish()
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(suppressor) = &self.suppressor {
+            suppressor.flush();
+        }
+        if self.summary {
+            crate::summary::finish();
+        }
+        self.flush();
+    }
+}
@@ -0,0 +1,88 @@
+//! Tests for `Buffered` output and its flushing behavior.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serif::Buffered;
+use serif::macros::*;
+use tracing_subscriber::fmt::MakeWriter;
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Log with a thread-local subscriber writing to `writer`.
+fn with_writer(writer: impl for<'a> MakeWriter<'a> + Send + Sync + 'static, f: impl FnOnce()) {
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .without_time()
+        .with_target(false)
+        .finish();
+    serif::tracing::subscriber::with_default(subscriber, f);
+}
+
+#[test]
+fn flushes_warnings_immediately() {
+    let output = Output::default();
+    let (writer, _guard) = Buffered::new(output.clone(), Duration::from_secs(3600));
+
+    with_writer(writer, || {
+        info!("one");
+        debug!("two");
+        assert_eq!(output.contents(), "");
+        warn!("three");
+        assert_eq!(output.contents(), " INFO one\n WARN three\n");
+        info!("four");
+        error!("five");
+    });
+    assert_eq!(output.contents(), " INFO one\n WARN three\n INFO four\nERROR five\n");
+}
+
+#[test]
+fn guard_flushes() {
+    let output = Output::default();
+    let (writer, guard) = Buffered::new(output.clone(), Duration::from_secs(3600));
+
+    with_writer(writer.clone(), || info!("one"));
+    assert_eq!(output.contents(), "");
+    guard.flush();
+    assert_eq!(output.contents(), " INFO one\n");
+
+    with_writer(writer, || info!("two"));
+    drop(guard);
+    assert_eq!(output.contents(), " INFO one\n INFO two\n");
+}
+
+#[test]
+fn flushes_periodically() {
+    let output = Output::default();
+    let (writer, _guard) = Buffered::new(output.clone(), Duration::from_millis(10));
+
+    with_writer(writer, || info!("one"));
+    for _ in 0..100 {
+        if !output.contents().is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(output.contents(), " INFO one\n");
+}
@@ -0,0 +1,86 @@
+//! Tests for the `serif::cli` command line flags.
+
+use clap::{CommandFactory, Parser, Subcommand};
+use serif::cli::Args;
+use serif::testing::CaptureBuilder;
+use serif::{ColorMode, Config, FixedClock, Format, Output};
+
+#[derive(Parser)]
+struct Cli {
+    #[command(flatten)]
+    log: Args,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    Run,
+}
+
+fn parse(args: &[&str]) -> Args {
+    Cli::try_parse_from(std::iter::once("app").chain(args.iter().copied())).unwrap().log
+}
+
+#[test]
+fn verbosity() {
+    assert_eq!(parse(&[]).verbosity(), 0);
+    assert_eq!(parse(&["-vvv"]).verbosity(), 3);
+    assert_eq!(parse(&["-q", "--quiet"]).verbosity(), -2);
+    assert_eq!(parse(&["-vv", "-q"]).verbosity(), 1);
+    // flags are global, so they can be given after a subcommand
+    assert_eq!(parse(&["run", "-v"]).verbosity(), 1);
+}
+
+#[test]
+fn options() {
+    let args = parse(&["--color", "never", "--log-file", "app.log", "--log-format=compact"]);
+    assert!(matches!(args.color, Some(ColorMode::Never)));
+    assert_eq!(args.log_file.as_deref(), Some("app.log".as_ref()));
+    assert_eq!(args.log_format, Some(Format::Compact));
+
+    let err = Cli::try_parse_from(["app", "--color", "sometimes"]).err().unwrap();
+    assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+
+    let help = Cli::command().render_help().to_string();
+    assert!(help.contains("--color <WHEN>"), "{help}");
+}
+
+/// The default directive and target levels, ignoring `RUST_LOG`.
+fn directives(config: &Config) -> String {
+    let config = config.clone().with_env_vars::<_, String>([]);
+    config.directives().iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+#[test]
+fn apply_args() {
+    let config = Config::from_args(&parse(&[]));
+    assert!(matches!(config.output(), Output::Stdout));
+    assert_eq!(config.color(), ColorMode::Auto);
+    assert_eq!(config.format(), Format::Full);
+    assert_eq!(directives(&config), "info");
+
+    let config = Config::new().with_verbosity(-1).apply_args(&parse(&["--log-file=app.log"]));
+    assert!(matches!(config.output(), Output::File(path) if path.as_os_str() == "app.log"));
+    assert_eq!(directives(&config), "warn");
+
+    let config = Config::from_args(&parse(&["-v", "--color=always", "--log-format=compact"]));
+    assert_eq!(config.format(), Format::Compact);
+    assert_eq!(config.color(), ColorMode::Always);
+    assert_eq!(directives(&config), "debug");
+}
+
+#[test]
+fn log_format_keeps_other_settings() {
+    let config = Config::new()
+        .with_target(false)
+        .with_clock(FixedClock::new("2024-02-29T13:14:15Z".parse().unwrap()))
+        .apply_args(&parse(&["--log-format=compact"]));
+    let capture = CaptureBuilder::new().with_config(&config).start();
+    tracing::warn!(target: "app", "message");
+
+    let lines = capture.lines();
+    assert_eq!(lines.len(), 1);
+    // the compact timestamp is in local time, so only the seconds are predictable
+    assert!(lines[0].ends_with(":15 W message"), "{lines:?}");
+}
@@ -0,0 +1,221 @@
+//! Tests for parsing configuration from strings and environment variables.
+
+use std::env;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serif::testing::CaptureBuilder;
+use serif::tracing::Level;
+use serif::{ColorMode, Config, FixedClock, Format, Output, TargetWidth, TimeFormat};
+
+#[test]
+fn parse_enums() {
+    assert!(matches!("always".parse(), Ok(ColorMode::Always)));
+    assert!(matches!("Never".parse(), Ok(ColorMode::Never)));
+    assert!(matches!("stderr".parse(), Ok(Output::Stderr)));
+    assert_eq!("COMPACT".parse(), Ok(Format::Compact));
+
+    let err = "sometimes".parse::<ColorMode>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid color mode 'sometimes', expected 'auto', 'always', or 'never'"
+    );
+}
+
+#[test]
+fn parse_time_format() {
+    let ts = "2024-02-29T13:14:15Z".parse().unwrap();
+    let render = |s: &str| s.parse::<TimeFormat>().unwrap().render(ts).to_string();
+
+    assert_eq!(render("none"), "");
+    assert_eq!(render("utc"), "[2024-02-29T13:14:15Z]");
+    assert_eq!(render("utc:%H:%M:%S"), "13:14:15");
+    assert_eq!(render("UTC:%Y"), "2024");
+
+    let err = "sometime".parse::<TimeFormat>().unwrap_err();
+    assert!(err.to_string().starts_with("invalid time format 'sometime', expected 'none'"));
+    let err = "utc:%J".parse::<TimeFormat>().unwrap_err();
+    assert!(err.to_string().starts_with("invalid time format 'utc:%J', expected a valid format"));
+}
+
+/// Set exactly these variables, removing all others that serif reads. Returns a guard which must
+/// be held while reading the environment, since tests run in parallel threads.
+fn set_env(vars: &[(&str, &str)]) -> MutexGuard<'static, ()> {
+    const VARS: &[&str] = &[
+        "SERIF_TIME",
+        "SERIF_COLOR",
+        "SERIF_TARGET",
+        "SERIF_SCOPE",
+        "SERIF_OUTPUT",
+        "SERIF_FORMAT",
+        "CLICOLOR",
+        "CLICOLOR_FORCE",
+        "RUST_LOG",
+        "MYTOOL_LOG",
+    ];
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    // SAFETY: all tests in this binary which use the environment hold the lock
+    unsafe {
+        for var in VARS {
+            env::remove_var(var);
+        }
+        for (name, val) in vars {
+            env::set_var(name, val);
+        }
+    }
+    guard
+}
+
+/// Format an event in a span using a Config's formatters, with a fixed clock.
+fn render(config: Config) -> String {
+    let config = config.with_clock(FixedClock::new("2024-02-29T13:14:15Z".parse().unwrap()));
+    let capture = CaptureBuilder::new().with_config(&config).start();
+    let span = tracing::info_span!("span");
+    let _enter = span.enter();
+    tracing::info!(target: "app::db", "message");
+    capture.lines().join("\n")
+}
+
+#[test]
+fn from_env() {
+    let _env = set_env(&[]);
+    let config = Config::from_env().unwrap();
+    assert!(matches!(config.output(), Output::Stdout));
+    assert_eq!(config.color(), ColorMode::Auto);
+    assert_eq!(config.format(), Format::Full);
+    assert_eq!(render(config), render(Config::new()));
+    drop(_env);
+
+    let _env = set_env(&[
+        ("SERIF_COLOR", ""),
+        ("SERIF_OUTPUT", "stderr"),
+        ("SERIF_TARGET", "no"),
+        ("SERIF_TIME", "utc"),
+    ]);
+    let config = Config::from_env().unwrap();
+    assert!(matches!(config.output(), Output::Stderr));
+    assert_eq!(config.color(), ColorMode::Auto);
+    assert_eq!(render(config), "[2024-02-29T13:14:15Z]  INFO span message");
+    drop(_env);
+
+    let color = || Config::from_env().unwrap().color();
+    let _env = set_env(&[("CLICOLOR_FORCE", "1")]);
+    assert_eq!(color(), ColorMode::Always);
+    drop(_env);
+    let _env = set_env(&[("CLICOLOR", "0")]);
+    assert_eq!(color(), ColorMode::Never);
+    drop(_env);
+    let _env = set_env(&[("CLICOLOR_FORCE", "1"), ("SERIF_COLOR", "never")]);
+    assert_eq!(color(), ColorMode::Never);
+    drop(_env);
+
+    let _env =
+        set_env(&[("SERIF_FORMAT", "compact"), ("SERIF_TIME", "utc:%H:%M"), ("SERIF_SCOPE", "0")]);
+    let config = Config::from_env().unwrap();
+    assert_eq!(config.format(), Format::Compact);
+    assert_eq!(render(config), "13:14 I a::db: message");
+    drop(_env);
+
+    let _env = set_env(&[("SERIF_SCOPE", "maybe")]);
+    let err = Config::from_env().unwrap_err();
+    assert_eq!(err.var(), "SERIF_SCOPE");
+    assert_eq!(err.to_string(), "SERIF_SCOPE: invalid boolean 'maybe', expected 'true' or 'false'");
+}
+
+#[test]
+fn format_env_keeps_other_settings() {
+    let config = Config::new()
+        .with_timestamp(TimeFormat::none())
+        .with_target(false)
+        .with_target_width(TargetWidth::Fixed(8));
+
+    let _env = set_env(&[("SERIF_FORMAT", "compact"), ("SERIF_TIME", "none")]);
+    let config = config.apply_env().unwrap();
+    assert_eq!(render(config), "I span    message");
+}
+
+#[test]
+fn filter_env_vars() {
+    let filter = |config: &Config| config.make_env_filter().to_string();
+    let config = Config::new().with_env_vars(["MYTOOL_LOG", "RUST_LOG"]);
+
+    let _env = set_env(&[]);
+    assert_eq!(filter(&config), "info");
+    drop(_env);
+
+    let _env = set_env(&[("RUST_LOG", "debug")]);
+    assert_eq!(filter(&config), "debug");
+    assert_eq!(filter(&Config::new().with_env_var("MYTOOL_LOG")), "info");
+    assert_eq!(filter(&Config::new().with_env_vars::<_, String>([])), "info");
+    drop(_env);
+
+    let _env = set_env(&[("RUST_LOG", "debug"), ("MYTOOL_LOG", "trace")]);
+    assert_eq!(filter(&config), "trace");
+    drop(_env);
+
+    // empty values are the same as unset
+    let _env = set_env(&[("RUST_LOG", "debug"), ("MYTOOL_LOG", "")]);
+    assert_eq!(filter(&config), "debug");
+}
+
+#[test]
+#[should_panic(expected = "Invalid MYTOOL_LOG filter string")]
+fn filter_env_var_invalid() {
+    let _env = set_env(&[("MYTOOL_LOG", "=bad=")]);
+    Config::new().with_env_var("MYTOOL_LOG").make_env_filter();
+}
+
+#[test]
+fn merge_env_directives() {
+    let directives = |config: &Config| {
+        config.directives().iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+    };
+    let config = Config::new().with_verbosity(1);
+    let merged = config.clone().with_env_merge(true);
+
+    let _env = set_env(&[]);
+    assert_eq!(directives(&config), "debug");
+    assert_eq!(directives(&merged), "debug");
+    drop(_env);
+
+    let _env = set_env(&[("RUST_LOG", "hyper=trace")]);
+    assert_eq!(directives(&config), "hyper=trace");
+    assert_eq!(directives(&merged), "debug,hyper=trace");
+    assert_eq!(merged.make_env_filter().to_string(), "hyper=trace,debug");
+    drop(_env);
+
+    // environment directives replace the default for the same target
+    let _env = set_env(&[("RUST_LOG", "warn,hyper=trace")]);
+    assert_eq!(merged.make_env_filter().to_string(), "hyper=trace,warn");
+}
+
+#[test]
+fn target_levels() {
+    let directives = |config: &Config| {
+        config.directives().iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+    };
+    let config = Config::new()
+        .with_verbosity(-1)
+        .with_target_level("hyper", Level::DEBUG)
+        .quiet_targets(["h2", "rustls"])
+        .with_verbosity_for("hyper", 2)
+        .with_verbosity_for("myapp::db", -3);
+
+    let _env = set_env(&[]);
+    assert_eq!(directives(&config), "warn,hyper=trace,h2=warn,rustls=warn,myapp::db=off");
+    drop(_env);
+
+    let _env = set_env(&[("RUST_LOG", "debug,h2=error")]);
+    assert_eq!(directives(&config), "debug,h2=error");
+    assert_eq!(
+        config.with_env_merge(true).make_env_filter().to_string(),
+        "myapp::db=off,rustls=warn,hyper=trace,h2=error,debug"
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid target for a filter directive: 'a=b'")]
+fn target_level_invalid() {
+    let _ = Config::new().with_target_level("a=b", Level::WARN);
+}
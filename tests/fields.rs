@@ -0,0 +1,175 @@
+//! Tests for `FieldFormatter` options.
+
+use serif::testing::{Capture, CaptureBuilder};
+use serif::{FieldFormatter, FieldLayout};
+
+fn capture(fields: FieldFormatter) -> Capture {
+    CaptureBuilder::new().with_field_formatter(fields).start()
+}
+
+#[test]
+fn target_rules_apply_to_sub_targets() {
+    let capture = capture(FieldFormatter::new().with_hidden_fields_for("app", ["secret"]));
+    tracing::info!(target: "app", secret = 1, id = 2, "a");
+    tracing::info!(target: "app::db", secret = 1, id = 2, "b");
+    tracing::info!(target: "application", secret = 1, id = 2, "c");
+    tracing::info!(target: "other", secret = 1, id = 2, "d");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO app: a [id=2]",
+            " INFO app::db: b [id=2]",
+            " INFO application: c [secret=1][id=2]",
+            " INFO other: d [secret=1][id=2]",
+        ]
+    );
+}
+
+#[test]
+fn most_specific_target_rule_wins() {
+    let capture = capture(
+        FieldFormatter::new()
+            .with_hidden_fields_for("app", ["a"])
+            .with_only_fields_for("app::db", ["a"]),
+    );
+    tracing::info!(target: "app::http", a = 1, b = 2, "http");
+    tracing::info!(target: "app::db::pool", a = 1, b = 2, "db");
+
+    assert_eq!(capture.lines(), [" INFO app::http: http [b=2]", " INFO app::db::pool: db [a=1]"]);
+}
+
+#[test]
+fn hidden_fields_in_events_and_spans() {
+    let capture = capture(FieldFormatter::new().with_hidden_fields(["token"]));
+    let span = tracing::info_span!(target: "app", "request", token = "abc", path = "/");
+    let _enter = span.enter();
+    tracing::info!(target: "app", token = "abc", user = "bob", "handled");
+    tracing::info!(target: "app", token = "abc");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO request[path=\"/\"]: app: handled [user=\"bob\"]",
+            " INFO request[path=\"/\"]: app: ",
+        ]
+    );
+}
+
+#[test]
+fn message_is_never_hidden() {
+    let capture = capture(
+        FieldFormatter::new().with_hidden_fields(["message"]).with_only_fields_for("app", ["id"]),
+    );
+    tracing::info!(target: "app", id = 1, extra = 2, "hello");
+
+    assert_eq!(capture.lines(), [" INFO app: hello [id=1]"]);
+}
+
+#[test]
+fn floats_keep_decimal_point() {
+    let capture = capture(FieldFormatter::new());
+    tracing::info!(target: "app", x = 1.0, y = 2.5, z = -0.0);
+
+    assert_eq!(capture.lines(), [" INFO app: [x=1.0][y=2.5][z=-0.0]"]);
+}
+
+#[test]
+fn default_styles_match_plain_dimmed_fields() {
+    let capture =
+        CaptureBuilder::new().with_field_formatter(FieldFormatter::new()).with_ansi(true).start();
+    let err = std::io::Error::other("broke");
+    tracing::info!(target: "app", n = 3, s = "x", "msg");
+    tracing::error!(target: "app", error = &err as &dyn std::error::Error);
+
+    let lines = capture.lines();
+    assert!(lines[0].ends_with("msg\x1b[2m [n=3]\x1b[0m\x1b[2m[s=\"x\"]\x1b[0m"), "{:?}", lines[0]);
+    assert!(lines[1].ends_with("\x1b[2;31m[error=broke]\x1b[0m"), "{:?}", lines[1]);
+}
+
+#[test]
+fn configured_styles_apply_to_values() {
+    use serif::{Color, Style};
+
+    let fields = FieldFormatter::new()
+        .with_key_style(Style::new())
+        .with_number_style(Color::Green.normal())
+        .with_error_style(Color::Red.bold());
+    let capture = CaptureBuilder::new().with_field_formatter(fields).with_ansi(true).start();
+    let err = std::io::Error::other("broke");
+    tracing::info!(target: "app", n = 3, error = &err as &dyn std::error::Error);
+
+    let lines = capture.lines();
+    assert!(
+        lines[0].ends_with("[n=\x1b[32m3\x1b[0m]\x1b[1;31m[error=broke]\x1b[0m"),
+        "{:?}",
+        lines[0]
+    );
+}
+
+#[test]
+fn logfmt_layout() {
+    let capture = capture(FieldFormatter::new().with_layout(FieldLayout::Logfmt));
+    let span = tracing::info_span!(target: "app", "req", id = 3, path = "/");
+    let _enter = span.enter();
+    tracing::info!(target: "app", a = 1, b = "x", "message");
+    tracing::info!(target: "app", a = 1, b = 2);
+    tracing::event!(target: "app", tracing::Level::INFO, a = 1, message = "middle", b = 2);
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO req{id=3 path=\"/\"}: app: message a=1 b=\"x\"",
+            " INFO req{id=3 path=\"/\"}: app: a=1 b=2",
+            " INFO req{id=3 path=\"/\"}: app: a=1 middle b=2",
+        ]
+    );
+}
+
+#[test]
+fn trailing_fields() {
+    for (layout, expected) in [
+        (FieldLayout::Brackets, " INFO app: message [a=1][b=2]"),
+        (FieldLayout::Logfmt, " INFO app: message a=1 b=2"),
+    ] {
+        let capture = capture(FieldFormatter::new().with_layout(layout).with_trailing_fields(true));
+        tracing::event!(target: "app", tracing::Level::INFO, a = 1, message = "message", b = 2);
+        assert_eq!(capture.lines(), [expected]);
+    }
+}
+
+#[test]
+fn block_fields_threshold() {
+    let capture = capture(FieldFormatter::new().with_block_fields(Some(2)));
+    tracing::info!(target: "app", a = 1, b = 2, "two fields");
+    tracing::info!(target: "app", a = 1, b = 2, c = 3, "three fields");
+    tracing::info!(target: "app", a = 1, b = 2, c = 3);
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO app: two fields [a=1][b=2]",
+            " INFO app: three fields",
+            "    [a=1][b=2][c=3]",
+            " INFO app: [a=1][b=2][c=3]",
+        ]
+    );
+}
+
+#[test]
+fn block_fields_always() {
+    let capture =
+        capture(FieldFormatter::new().with_layout(FieldLayout::Logfmt).with_block_fields(Some(0)));
+    tracing::info!(target: "app", a = 1, "one field");
+    tracing::info!(target: "app", "no fields");
+
+    assert_eq!(capture.lines(), [" INFO app: one field", "    a=1", " INFO app: no fields"]);
+}
+
+#[test]
+fn unquoted_strings() {
+    let capture = capture(FieldFormatter::new().with_unquoted_strings(true));
+    tracing::info!(target: "app", simple = "bob", spaced = "two words", empty = "", "msg");
+
+    assert_eq!(capture.lines(), [" INFO app: msg [simple=bob][spaced=\"two words\"][empty=\"\"]"]);
+}
@@ -0,0 +1,171 @@
+//! Tests for `EventFormatter` options.
+
+use serif::testing::{Capture, CaptureBuilder};
+use serif::{EventFormatter, Format, LevelLabels, TargetWidth, TimeFormat};
+
+fn capture(formatter: EventFormatter) -> Capture {
+    CaptureBuilder::new().with_event_formatter(formatter.with_timestamp(TimeFormat::none())).start()
+}
+
+#[test]
+fn format_preset_keeps_other_options() {
+    let formatter = EventFormatter::new()
+        .with_target_width(TargetWidth::Fixed(8))
+        .with_scope(false)
+        .with_format(Format::Compact);
+    let capture = capture(formatter);
+    let span = tracing::info_span!("span");
+    let _enter = span.enter();
+    tracing::info!(target: "app::db", "compact");
+
+    assert_eq!(capture.lines(), ["I a::db:  compact"]);
+}
+
+#[test]
+fn full_preset_resets_compact_options() {
+    let formatter = EventFormatter::compact().with_format(Format::Full);
+    let capture = capture(formatter);
+    tracing::info!(target: "app::db", "full");
+
+    assert_eq!(capture.lines(), [" INFO app::db: full"]);
+}
+
+#[test]
+fn emoji_labels_align_by_columns() {
+    // emoji are two columns wide, including a text symbol with an emoji variation selector
+    let labels = LevelLabels::custom("T", "D", "ℹ\u{fe0f}", "⚠\u{fe0f}", "🔥");
+    let formatter = EventFormatter::new().with_level_labels(labels).with_wrap_width(Some(30));
+    let capture = capture(formatter);
+    tracing::info!(target: "app", "info");
+    tracing::error!(target: "app", "error");
+    tracing::debug!(target: "app", "the quick brown fox jumps over the lazy dog");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            "ℹ\u{fe0f} app: info",
+            "🔥 app: error",
+            " D app: the quick brown fox",
+            "        jumps over the lazy",
+            "        dog",
+        ]
+    );
+}
+
+#[test]
+fn fixed_width_pads_and_truncates_targets() {
+    let capture = capture(EventFormatter::new().with_target_width(TargetWidth::Fixed(10)));
+    tracing::info!(target: "app", "short");
+    tracing::info!(target: "app::network::http", "long");
+
+    assert_eq!(capture.lines(), [" INFO app:      short", " INFO …k::http: long"]);
+}
+
+#[test]
+fn fixed_width_truncates_long_scopes() {
+    let capture = capture(EventFormatter::new().with_target_width(TargetWidth::Fixed(6)));
+    let span = tracing::info_span!("spanname_long", id = 3);
+    let _enter = span.enter();
+    tracing::info!(target: "path", "in span");
+
+    let span = tracing::info_span!("s");
+    let _enter = span.enter();
+    tracing::info!(target: "p", "nested");
+
+    assert_eq!(capture.lines(), [" INFO … …h: in span", " INFO …s p: nested"]);
+}
+
+#[test]
+fn fixed_width_truncates_target_before_scope() {
+    let capture = capture(EventFormatter::new().with_target_width(TargetWidth::Fixed(20)));
+    let span = tracing::info_span!("spanname_long", id = 3);
+    let _enter = span.enter();
+    tracing::info!(target: "app::db", "query");
+    tracing::info!(target: "app::db::pool::connection", "connect");
+
+    assert_eq!(
+        capture.lines(),
+        [" INFO …me_long[id=3]: …b: query", " INFO …me_long[id=3]: …n: connect"]
+    );
+}
+
+#[test]
+fn adaptive_width_grows_up_to_max() {
+    let capture =
+        capture(EventFormatter::new().with_target_width(TargetWidth::Adaptive { max: 12 }));
+    tracing::info!(target: "app", "one");
+    tracing::info!(target: "app::db", "two");
+    tracing::info!(target: "app", "three");
+    tracing::info!(target: "app::db::pool::connection", "four");
+    tracing::info!(target: "app", "five");
+    let span = tracing::info_span!("spanname_long");
+    let _enter = span.enter();
+    tracing::info!(target: "app", "six");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO app: one",
+            " INFO app::db: two",
+            " INFO app:     three",
+            " INFO …onnection: four",
+            " INFO app:        five",
+            " INFO …e_long …p: six",
+        ]
+    );
+}
+
+#[test]
+fn wrap_at_word_boundaries() {
+    // the smallest width that wraps, with the message starting at column 11
+    let capture = capture(EventFormatter::new().with_wrap_width(Some(31)));
+    tracing::info!(target: "app", "the quick brown fox jumps over the lazy dog");
+    tracing::info!(target: "app", "exactly twenty chars");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO app: the quick brown fox",
+            "           jumps over the lazy",
+            "           dog",
+            " INFO app: exactly twenty chars",
+        ]
+    );
+}
+
+#[test]
+fn wrap_indents_after_explicit_newlines() {
+    let capture = capture(EventFormatter::new().with_wrap_width(Some(40)));
+    tracing::info!(target: "app", "first line\nsecond line\n\n  indented line");
+
+    assert_eq!(
+        capture.lines(),
+        [" INFO app: first line", "           second line", "", "             indented line"]
+    );
+}
+
+#[test]
+fn wrap_keeps_long_words_whole() {
+    let capture = capture(EventFormatter::new().with_wrap_width(Some(32)));
+    tracing::info!(target: "app", "see https://example.com/a/very/long/path for details");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            " INFO app: see",
+            "           https://example.com/a/very/long/path",
+            "           for details",
+        ]
+    );
+}
+
+#[test]
+fn no_wrap_when_too_narrow() {
+    // the message starts at column 16, so there must be at least 20 more columns to wrap
+    let capture = capture(EventFormatter::new().with_wrap_width(Some(35)));
+    let span = tracing::info_span!("span");
+    let _enter = span.enter();
+    tracing::info!(target: "app", "the quick brown fox jumps over the lazy dog");
+
+    assert_eq!(capture.lines(), [" INFO span app: the quick brown fox jumps over the lazy dog"]);
+}
@@ -0,0 +1,29 @@
+//! Tests for detecting whether stderr is connected to the journal. This modifies the environment,
+//! so it's the only test in this binary.
+#![cfg(unix)]
+
+use std::env;
+use std::fs::File;
+use std::io::{self, IsTerminal};
+use std::os::fd::AsFd;
+use std::os::unix::fs::MetadataExt;
+
+use serif::Journald;
+
+#[test]
+fn stderr_is_journal() {
+    // SAFETY: this is the only test in this binary, so no other threads access the environment
+    unsafe { env::remove_var("JOURNAL_STREAM") };
+    assert!(!Journald::stderr_is_journal());
+
+    let fd = io::stderr().as_fd().try_clone_to_owned().unwrap();
+    let meta = File::from(fd).metadata().unwrap();
+
+    unsafe { env::set_var("JOURNAL_STREAM", format!("{}:{}", meta.dev(), meta.ino() + 1)) };
+    assert!(!Journald::stderr_is_journal());
+
+    unsafe { env::set_var("JOURNAL_STREAM", format!("{}:{}", meta.dev(), meta.ino())) };
+    assert_eq!(Journald::stderr_is_journal(), !io::stderr().is_terminal());
+
+    unsafe { env::remove_var("JOURNAL_STREAM") };
+}
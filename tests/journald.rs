@@ -0,0 +1,92 @@
+//! Tests for journald output, using a local socket in place of the journal.
+#![cfg(unix)]
+
+use std::collections::BTreeMap;
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+use serif::Journald;
+use serif::macros::*;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// A bound socket standing in for the journal, which is removed when dropped.
+struct Journal {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl Journal {
+    fn new() -> Self {
+        let path = env::temp_dir().join(format!("serif-test-{}-journal.sock", process::id()));
+        let _ = fs::remove_file(&path);
+        let socket = UnixDatagram::bind(&path).unwrap();
+        Self { socket, path }
+    }
+
+    /// Receive an entry and parse its fields, in either the simple or binary-safe form.
+    fn recv(&self) -> BTreeMap<String, String> {
+        let mut buf = vec![0u8; 65536];
+        let len = self.socket.recv(&mut buf).unwrap();
+        let mut rest = &buf[..len];
+        let mut fields = BTreeMap::new();
+        while !rest.is_empty() {
+            let end = rest.iter().position(|&b| b == b'=' || b == b'\n').unwrap();
+            let name = String::from_utf8(rest[..end].to_vec()).unwrap();
+            let value = if rest[end] == b'=' {
+                let len = rest[end..].iter().position(|&b| b == b'\n').unwrap() - 1;
+                let value = &rest[end + 1..end + 1 + len];
+                rest = &rest[end + len + 2..];
+                value
+            } else {
+                let len = u64::from_le_bytes(rest[end + 1..end + 9].try_into().unwrap()) as usize;
+                let value = &rest[end + 9..end + 9 + len];
+                assert_eq!(rest[end + 9 + len], b'\n');
+                rest = &rest[end + 10 + len..];
+                value
+            };
+            fields.insert(name, String::from_utf8(value.to_vec()).unwrap());
+        }
+        fields
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[test]
+fn structured_fields() {
+    let journal = Journal::new();
+    let layer = Journald::new().with_path(&journal.path).with_app_name("myapp").connect().unwrap();
+    let subscriber = tracing_subscriber::registry().with(layer);
+
+    let line = serif::tracing::subscriber::with_default(subscriber, || {
+        let _outer = info_span!("outer", id = 1).entered();
+        let _inner = info_span!("inner").entered();
+        warn!(
+            target: "myapp::db",
+            { user.name = "alice", count = 3, priority = true },
+            "hello\nworld"
+        );
+        line!() - 5
+    });
+
+    let fields = journal.recv();
+    let expected = [
+        ("MESSAGE", "hello\nworld"),
+        ("PRIORITY", "4"),
+        ("CODE_FILE", file!()),
+        ("CODE_LINE", &line.to_string()),
+        ("TARGET", "myapp::db"),
+        ("SPANS", "outer:inner"),
+        ("SYSLOG_IDENTIFIER", "myapp"),
+        ("USER_NAME", "alice"),
+        ("COUNT", "3"),
+        ("F_PRIORITY", "true"),
+    ];
+    let expected = expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    assert_eq!(fields, expected);
+}
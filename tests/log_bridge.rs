@@ -0,0 +1,49 @@
+//! Records from the `log` crate should look exactly like tracing events once they've been bridged
+//! by the `LogTracer` that `Config::init` installs. This installs a global subscriber.
+
+use std::{env, fs, process};
+
+use serif::{ColorMode, Config, Output, TimeFormat};
+use tracing::Level;
+
+#[test]
+fn log_bridge() {
+    let path = env::temp_dir().join(format!("serif-test-{}-log-bridge.log", process::id()));
+    let _ = fs::remove_file(&path);
+    let guard = Config::new()
+        .with_output(Output::File(path.clone()))
+        .with_color(ColorMode::Never)
+        .with_timestamp(TimeFormat::none())
+        .with_env_var("SERIF_TEST_LOG_BRIDGE")
+        .with_default(Level::DEBUG)
+        .with_target_level("noisy_dependency", Level::WARN)
+        .init();
+
+    // the bridge's max level follows the most verbose level enabled by the filter
+    assert_eq!(log::max_level(), log::LevelFilter::Debug);
+
+    log::info!(target: "some_dependency::client", "connected to {}", "example.com");
+    tracing::info!(target: "some_dependency::client", "connected to {}", "example.com");
+    log::warn!("no explicit target");
+    tracing::warn!("no explicit target");
+
+    log::info!(target: "noisy_dependency::pool", "hidden");
+    log::warn!(target: "noisy_dependency::pool", "shown");
+    log::trace!(target: "other_dependency", "hidden");
+    log::debug!(target: "other_dependency", "shown");
+
+    drop(guard);
+    let log = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        [
+            " INFO some_dependency::client: connected to example.com",
+            " INFO some_dependency::client: connected to example.com",
+            " WARN log_bridge: no explicit target",
+            " WARN log_bridge: no explicit target",
+            " WARN noisy_dependency::pool: shown",
+            "DEBUG other_dependency: shown",
+        ]
+    );
+}
@@ -0,0 +1,32 @@
+//! If another `log` logger is already installed, `Config::init` panics before installing anything
+//! else globally. This installs a global logger.
+
+use std::panic;
+
+use serif::Config;
+
+struct OtherLogger;
+
+impl log::Log for OtherLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        false
+    }
+
+    fn log(&self, _record: &log::Record) {}
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn log_bridge_conflict() {
+    log::set_logger(&OtherLogger).unwrap();
+
+    let result = panic::catch_unwind(|| Config::new().init());
+    let err = result.expect_err("init should panic when another logger is installed");
+    let message = err.downcast_ref::<String>().map(String::as_str).unwrap_or_default();
+    assert!(message.starts_with("Unable to install the log bridge"), "{message}");
+
+    // the global subscriber wasn't installed, so there's no output that needed a guard
+    let subscriber = tracing::subscriber::NoSubscriber::default();
+    assert!(tracing::subscriber::set_global_default(subscriber).is_ok());
+}
@@ -0,0 +1,137 @@
+//! Tests for the `NonBlocking` background writer and its overflow policies.
+
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serif::{Guard, NonBlocking, OverflowPolicy};
+use tracing_subscriber::fmt::MakeWriter;
+
+fn log(writer: &NonBlocking, msg: &str) {
+    writeln!(writer.make_writer(), "{msg}").unwrap();
+}
+
+/// A writer which can be paused, to simulate slow output.
+struct SlowWriter {
+    output: Arc<Mutex<Vec<u8>>>,
+    gate: &'static Mutex<()>,
+    started: Sender<()>,
+}
+
+impl Write for SlowWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.started.send(()).unwrap();
+        let _gate = self.gate.lock().unwrap();
+        self.output.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Harness {
+    writer: NonBlocking,
+    guard: Guard,
+    output: Arc<Mutex<Vec<u8>>>,
+    gate: &'static Mutex<()>,
+    started: Receiver<()>,
+}
+
+impl Harness {
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let gate = Box::leak(Box::new(Mutex::new(())));
+        let (started_tx, started) = mpsc::channel();
+        let slow = SlowWriter { output: output.clone(), gate, started: started_tx };
+        let (writer, guard) = NonBlocking::new(slow, capacity, policy);
+        Self { writer, guard, output, gate, started }
+    }
+
+    /// Pause the writer thread while it's writing the first event.
+    fn pause(&self) -> MutexGuard<'static, ()> {
+        let gate = self.gate.lock().unwrap();
+        log(&self.writer, "1");
+        self.started.recv().unwrap();
+        gate
+    }
+
+    fn output(&self) -> String {
+        String::from_utf8(self.output.lock().unwrap().clone()).unwrap()
+    }
+}
+
+#[test]
+fn writes_in_background() {
+    let h = Harness::new(16, OverflowPolicy::Block);
+    for i in 1..=5 {
+        log(&h.writer, &i.to_string());
+    }
+    h.guard.flush();
+    assert_eq!(h.output(), "1\n2\n3\n4\n5\n");
+    assert_eq!(h.guard.dropped_events(), 0);
+}
+
+#[test]
+fn drop_newest() {
+    let h = Harness::new(2, OverflowPolicy::DropNewest);
+    let gate = h.pause();
+    for msg in ["2", "3", "4", "5"] {
+        log(&h.writer, msg);
+    }
+    drop(gate);
+    h.guard.flush();
+    assert_eq!(h.output(), "1\n2\n3\n");
+    assert_eq!(h.guard.dropped_events(), 2);
+    assert_eq!(h.writer.dropped_events(), 2);
+}
+
+#[test]
+fn drop_oldest() {
+    let h = Harness::new(2, OverflowPolicy::DropOldest);
+    let gate = h.pause();
+    for msg in ["2", "3", "4", "5"] {
+        log(&h.writer, msg);
+    }
+    drop(gate);
+    h.guard.flush();
+    assert_eq!(h.output(), "1\n4\n5\n");
+    assert_eq!(h.guard.dropped_events(), 2);
+}
+
+#[test]
+fn block() {
+    let h = Harness::new(2, OverflowPolicy::Block);
+    let gate = h.pause();
+    thread::scope(|s| {
+        let writer = &h.writer;
+        let logger = s.spawn(move || {
+            for msg in ["2", "3", "4", "5"] {
+                log(writer, msg);
+            }
+        });
+        thread::sleep(Duration::from_millis(50));
+        assert!(!logger.is_finished(), "logging should block while the queue is full");
+        drop(gate);
+    });
+    h.guard.flush();
+    assert_eq!(h.output(), "1\n2\n3\n4\n5\n");
+    assert_eq!(h.guard.dropped_events(), 0);
+}
+
+#[test]
+fn guard_flushes_on_drop() {
+    let h = Harness::new(16, OverflowPolicy::Block);
+    let gate = h.pause();
+    log(&h.writer, "2");
+    let output = h.output.clone();
+    thread::scope(|s| {
+        s.spawn(|| drop(h.guard));
+        thread::sleep(Duration::from_millis(20));
+        drop(gate);
+    });
+    assert_eq!(String::from_utf8(output.lock().unwrap().clone()).unwrap(), "1\n2\n");
+}
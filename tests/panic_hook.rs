@@ -0,0 +1,44 @@
+//! Tests for the panic hook, which is installed globally by `Config::init`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{panic, thread};
+
+use serif::testing::{self, EventMatcher};
+use serif::{Config, Output};
+use tracing::Level;
+use tracing::level_filters::LevelFilter;
+
+#[test]
+fn panic_hook() {
+    static PREV_HOOK_CALLED: AtomicBool = AtomicBool::new(false);
+    panic::set_hook(Box::new(|_| PREV_HOOK_CALLED.store(true, Ordering::Relaxed)));
+
+    // The global subscriber only installs the hook, events are captured on a named thread.
+    let _guard = Config::new()
+        .with_output(Output::Stderr)
+        .with_default(LevelFilter::OFF)
+        .with_log_bridge(false)
+        .with_panic_hook(true)
+        .init();
+
+    let (event, line) = thread::Builder::new()
+        .name("worker".into())
+        .spawn(|| {
+            let capture = testing::capture();
+            let line = line!() + 1;
+            let result = panic::catch_unwind(|| panic!("oh no: {}", 42));
+            assert!(result.is_err());
+            let matcher = EventMatcher::new().with_target("panic").with_level(Level::ERROR);
+            (capture.find(&matcher), line)
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(PREV_HOOK_CALLED.load(Ordering::Relaxed), "the previous hook wasn't called");
+
+    let event = event.expect("no panic event was logged");
+    assert_eq!(event.message(), "oh no: 42");
+    assert_eq!(event.field("thread"), Some("worker"));
+    let location = event.field("location").expect("no location field");
+    assert!(location.starts_with(&format!("{}:{line}:", file!())), "{location}");
+}
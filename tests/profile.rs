@@ -0,0 +1,144 @@
+//! Tests for environment profiles and sd-daemon level prefixes.
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serif::testing::CaptureBuilder;
+use serif::{Config, EventFormatter, FixedClock, Output, Profile, TimeFormat};
+use tracing_subscriber::filter::LevelFilter;
+
+/// Set exactly these variables, removing all others that profile detection reads. Returns a guard
+/// which must be held while reading the environment, since tests run in parallel threads.
+fn set_env(vars: &[(&str, &str)]) -> MutexGuard<'static, ()> {
+    const VARS: &[&str] = &["JOURNAL_STREAM", "container", "KUBERNETES_SERVICE_HOST"];
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    // SAFETY: all tests in this binary which use the environment hold the lock
+    unsafe {
+        for var in VARS {
+            env::remove_var(var);
+        }
+        for (name, val) in vars {
+            env::set_var(name, val);
+        }
+    }
+    guard
+}
+
+/// The `JOURNAL_STREAM` value which marks stdout as connected to the journal.
+#[cfg(unix)]
+fn stdout_journal_stream() -> String {
+    use std::fs::File;
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::MetadataExt;
+
+    let fd = io::stdout().as_fd().try_clone_to_owned().unwrap();
+    let meta = File::from(fd).metadata().unwrap();
+    format!("{}:{}", meta.dev(), meta.ino())
+}
+
+/// Format a warning using a Config's formatters, with a fixed clock.
+fn render(config: Config, ansi: bool) -> String {
+    let config = config
+        .with_timestamp(TimeFormat::utc_custom("%H:%M:%S"))
+        .with_clock(FixedClock::new("2024-02-29T13:14:15Z".parse().unwrap()));
+    let capture = CaptureBuilder::new().with_config(&config).with_ansi(ansi).start();
+    tracing::warn!(target: "app", "message");
+    capture.lines().join("\n")
+}
+
+#[test]
+fn level_prefix() {
+    let event_formatter =
+        EventFormatter::new().with_timestamp(TimeFormat::none()).with_level_prefix(true);
+    let capture = CaptureBuilder::new()
+        .with_event_formatter(event_formatter)
+        .with_default(LevelFilter::TRACE)
+        .start();
+    tracing::error!(target: "app", "error");
+    tracing::warn!(target: "app", "warn");
+    tracing::info!(target: "app", "info");
+    tracing::debug!(target: "app", "debug");
+    tracing::trace!(target: "app", "trace");
+
+    assert_eq!(
+        capture.lines(),
+        [
+            "<3>ERROR app: error",
+            "<4> WARN app: warn",
+            "<6> INFO app: info",
+            "<7>DEBUG app: debug",
+            "<7>TRACE app: trace",
+        ]
+    );
+}
+
+#[test]
+fn detect() {
+    // files are never collected by journald or a container runtime
+    let _env = set_env(&[("JOURNAL_STREAM", "1:1"), ("container", "podman")]);
+    let output = Output::File("/tmp/serif-profile.log".into());
+    assert_eq!(Profile::detect(&output), Profile::Standard);
+}
+
+#[test]
+#[cfg(unix)]
+fn detect_journal() {
+    let _env = set_env(&[("JOURNAL_STREAM", &stdout_journal_stream()), ("container", "podman")]);
+    // terminals always use the standard profile, and the journal takes precedence over containers
+    let expected = if io::stdout().is_terminal() { Profile::Standard } else { Profile::Journal };
+    assert_eq!(Profile::detect(&Output::Stdout), expected);
+    drop(_env);
+
+    // JOURNAL_STREAM only applies to the stream it identifies
+    let _env = set_env(&[("JOURNAL_STREAM", "0:0")]);
+    assert_ne!(Profile::detect(&Output::Stdout), Profile::Journal);
+}
+
+#[test]
+fn detect_container() {
+    let _env = set_env(&[("container", "podman")]);
+    let expected = if io::stdout().is_terminal() { Profile::Standard } else { Profile::Container };
+    assert_eq!(Profile::detect(&Output::Stdout), expected);
+    drop(_env);
+
+    let _env = set_env(&[("KUBERNETES_SERVICE_HOST", "10.0.0.1")]);
+    assert_eq!(Profile::detect(&Output::Stdout), expected);
+}
+
+#[test]
+fn profiles() {
+    let _env = set_env(&[]);
+    let config = || Config::new().with_output(Output::File("/tmp/serif-profile.log".into()));
+    assert_eq!(
+        render(config().with_profile(Profile::Standard), false),
+        "13:14:15  WARN app: message"
+    );
+    assert_eq!(render(config().with_profile(Profile::Journal), false), "<4> WARN app: message");
+    assert_eq!(render(config().with_profile(Profile::Container), false), " WARN app: message");
+    // files always resolve to the standard profile
+    assert_eq!(render(config().with_profile(Profile::Auto), false), "13:14:15  WARN app: message");
+}
+
+#[test]
+fn journal_prefix_before_colors() {
+    let _env = set_env(&[]);
+    let config = Config::new().with_profile(Profile::Journal);
+    // the prefix comes before any escape sequences, so that journald can parse it
+    assert_eq!(render(config, true), "<4>\x1b[33m WARN \x1b[0m\x1b[2;34mapp\x1b[0m: message");
+}
+
+#[test]
+#[cfg(unix)]
+fn auto_profile_detects_journal() {
+    let _env = set_env(&[("JOURNAL_STREAM", &stdout_journal_stream())]);
+    let config = Config::new().with_output(Output::Stdout).with_profile(Profile::Auto);
+    let expected = if io::stdout().is_terminal() {
+        "13:14:15  WARN app: message"
+    } else {
+        "<4> WARN app: message"
+    };
+    assert_eq!(render(config, false), expected);
+}
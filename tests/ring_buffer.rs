@@ -0,0 +1,80 @@
+//! Tests for `RingBuffer`, recording events at a separate level from the output.
+
+use std::{env, fs, process};
+
+use serif::macros::*;
+use serif::{ColorMode, Config, Output, RingBuffer, TargetWidth, TimeFormat};
+use tracing::Level;
+
+#[test]
+fn ring_buffer() {
+    let ring = RingBuffer::new(3);
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(ring.clone())
+        .with_ansi(false)
+        .without_time()
+        .with_target(false)
+        .finish();
+    serif::tracing::subscriber::with_default(subscriber, || {
+        for i in 0..5 {
+            info!("event {i}");
+        }
+    });
+
+    assert_eq!(ring.capacity(), 3);
+    assert_eq!(ring.len(), 3);
+    let mut out = Vec::new();
+    ring.write_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), " INFO event 2\n INFO event 3\n INFO event 4\n");
+
+    assert_eq!(ring.drain(), [" INFO event 2\n", " INFO event 3\n", " INFO event 4\n"]);
+    assert!(ring.is_empty());
+}
+
+#[test]
+fn separate_level() {
+    let dir = env::temp_dir();
+    let log_path = dir.join(format!("serif-test-{}-ring.log", process::id()));
+    let dump_path = dir.join(format!("serif-test-{}-ring-dump.log", process::id()));
+    let _ = fs::remove_file(&log_path);
+
+    let ring = RingBuffer::new(100);
+    let _guard = Config::new()
+        .with_output(Output::File(log_path.clone()))
+        .with_color(ColorMode::Never)
+        .with_timestamp(TimeFormat::none())
+        .with_target_width(TargetWidth::Adaptive { max: 40 })
+        .with_log_bridge(false)
+        .with_ring_buffer(ring.clone(), Level::TRACE)
+        .init();
+
+    // the output's alignment isn't widened by events that only the ring buffer records
+    trace!(target: "ring_buffer::long_target", "starting");
+    trace!(path = "/tmp", "opening");
+    debug!("opened");
+    info!("done");
+
+    ring.write_to_file(&dump_path).unwrap();
+    let log = fs::read_to_string(&log_path).unwrap();
+    let dump = fs::read_to_string(&dump_path).unwrap();
+    let _ = fs::remove_file(&log_path);
+    let _ = fs::remove_file(&dump_path);
+
+    assert_eq!(log, " INFO ring_buffer: done\n");
+    assert_eq!(
+        dump.lines().collect::<Vec<_>>(),
+        [
+            "TRACE ring_buffer::long_target: starting",
+            "TRACE ring_buffer:              opening [path=\"/tmp\"]",
+            "DEBUG ring_buffer:              opened",
+            " INFO ring_buffer:              done",
+        ]
+    );
+    assert_eq!(ring.len(), 4);
+}
+
+#[test]
+#[should_panic(expected = "RingBuffer capacity must be non-zero")]
+fn zero_capacity() {
+    let _ = RingBuffer::new(0);
+}
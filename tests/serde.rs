@@ -0,0 +1,120 @@
+//! Tests for loading `Config` from configuration files with serde.
+
+use serde::{Deserialize, Serialize};
+use serif::testing::CaptureBuilder;
+use serif::{ColorMode, Config, FixedClock, Format, Output, TimeFormat};
+
+#[derive(Deserialize, Serialize)]
+struct AppConfig {
+    #[serde(default)]
+    logging: Config,
+}
+
+fn load(s: &str) -> Result<Config, toml::de::Error> {
+    toml::from_str::<AppConfig>(s).map(|app| app.logging)
+}
+
+/// The default directive and target levels, ignoring `RUST_LOG`.
+fn directives(config: &Config) -> String {
+    let config = config.clone().with_env_vars::<_, String>([]);
+    config.directives().iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// Format an event using a Config's formatters, with a fixed clock.
+fn render(config: &Config) -> String {
+    let config =
+        config.clone().with_clock(FixedClock::new("2024-02-29T13:14:15Z".parse().unwrap()));
+    let capture = CaptureBuilder::new().with_config(&config).start();
+    tracing::info!(target: "app::db", "message");
+    capture.lines().join("\n")
+}
+
+#[test]
+fn load_config() {
+    for config in [load("").unwrap(), load("[logging]").unwrap()] {
+        assert!(matches!(config.output(), Output::Stdout));
+        assert_eq!(config.color(), ColorMode::Auto);
+        assert_eq!(config.format(), Format::Full);
+        assert_eq!(directives(&config), "info");
+        assert_eq!(render(&config), render(&Config::new()));
+    }
+
+    let config = load(
+        r#"
+        [logging]
+        level = "debug"
+        targets = { hyper = "warn", "myapp::db" = "trace" }
+        color = "never"
+        timestamp = "utc:%H:%M:%S"
+        output = "/var/log/app.log"
+        format = "compact"
+        "#,
+    )
+    .unwrap();
+    assert!(
+        matches!(config.output(), Output::File(path) if path.as_os_str() == "/var/log/app.log")
+    );
+    assert_eq!(config.color(), ColorMode::Never);
+    assert_eq!(config.format(), Format::Compact);
+    assert_eq!(directives(&config), "debug,hyper=warn,myapp::db=trace");
+    assert_eq!(render(&config), "13:14:15 I a::db: message");
+
+    // the format preset's timestamp is used unless one is given
+    let config = load("logging.format = 'compact'").unwrap();
+    assert_eq!(render(&config), render(&Config::new().with_format(Format::Compact)));
+}
+
+#[test]
+fn round_trip() {
+    let config = Config::new()
+        .with_format(Format::Compact)
+        .with_timestamp(TimeFormat::utc_custom("%H:%M"))
+        .quiet_targets(["h2"])
+        .with_verbosity(-1)
+        .with_output(Output::Stderr);
+    let s = toml::to_string(&AppConfig { logging: config.clone() }).unwrap();
+    assert_eq!(
+        s,
+        r#"[logging]
+level = "warn"
+color = "auto"
+timestamp = "utc:%H:%M"
+output = "stderr"
+format = "compact"
+
+[logging.targets]
+h2 = "warn"
+"#
+    );
+
+    let loaded = load(&s).unwrap();
+    assert!(matches!(loaded.output(), Output::Stderr));
+    assert_eq!(loaded.color(), config.color());
+    assert_eq!(loaded.format(), config.format());
+    assert_eq!(directives(&loaded), directives(&config));
+    assert_eq!(render(&loaded), render(&config));
+    assert_eq!(render(&loaded), "13:14 I a::db: message");
+}
+
+#[test]
+fn errors() {
+    let err = |s: &str| load(s).unwrap_err().to_string();
+
+    let msg = err("[logging]\ncolour = 'never'");
+    assert!(msg.contains("unknown field `colour`"), "{msg}");
+    assert!(msg.contains("line 2"), "{msg}");
+
+    let msg = err("[logging]\ncolor = 'sometimes'");
+    assert!(msg.contains("invalid color mode 'sometimes'"), "{msg}");
+    assert!(msg.contains("color = 'sometimes'"), "{msg}");
+
+    let msg = err("[logging]\ntimestamp = 'utc:%J'");
+    assert!(msg.contains("invalid time format 'utc:%J'"), "{msg}");
+
+    let msg = err("[logging.targets]\nhyper = 'loud'");
+    assert!(msg.contains("invalid level 'loud' for target 'hyper'"), "{msg}");
+
+    let msg = err("[logging]\nlevel = 'a=b=c'");
+    assert!(msg.contains("level = 'a=b=c'"), "{msg}");
+    assert!(msg.contains("error parsing level filter"), "{msg}");
+}
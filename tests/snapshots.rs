@@ -0,0 +1,264 @@
+//! Golden-output tests for combinations of `EventFormatter` and `FieldFormatter` options.
+//!
+//! Each test renders the same set of events with every combination of target, scope, and
+//! timestamp options, then with every combination of field layout options, then with a list of
+//! other formatter variants, and compares the result to a file in `tests/snapshots`. ANSI escapes
+//! are written as `\e` to keep the files readable.
+//!
+//! If a formatting change is intentional, regenerate the snapshots by running the tests with
+//! `SERIF_BLESS=1` set in the environment and review the diff.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+use std::sync::Once;
+use std::time::Duration;
+
+use jiff::Timestamp;
+use serif::testing::CaptureBuilder;
+use serif::{
+    Color, EventFormatter, FieldFormatter, FieldLayout, FixedClock, Format, LevelLabels,
+    ManualClock, Style, TargetWidth, TimeFormat,
+};
+use tracing::Level;
+
+/// The fixed time used for all timestamps.
+fn fixed_time() -> Timestamp {
+    "2024-02-29T13:14:15.678Z".parse().unwrap()
+}
+
+#[derive(Debug)]
+struct TestError;
+
+impl fmt::Display for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("something broke")
+    }
+}
+
+impl Error for TestError {}
+
+/// Emit a variety of events that exercise the formatters.
+fn emit_events() {
+    static LOG_TRACER: Once = Once::new();
+    LOG_TRACER.call_once(|| tracing_log::LogTracer::init().unwrap());
+
+    tracing::info!(target: "app", "message only");
+    tracing::info!(target: "app", count = 3, label = "three", "fields after the message");
+    tracing::event!(target: "app", Level::WARN, before = true, message = "fields around", after = 1.5);
+    tracing::debug!(target: "app", count = 3, label = "three");
+    tracing::error!(target: "app", error = &TestError as &dyn Error, "operation failed");
+    tracing::info!(
+        target: "app::database::connection_pool",
+        token = "secret",
+        path = "/var/lib/app data",
+        "a much longer message from a deeply nested module, which is wrapped when a wrap width is set",
+    );
+    {
+        let outer = tracing::info_span!(target: "app", "outer", id = 7);
+        let _outer = outer.enter();
+        tracing::trace!(target: "app::inner", "in one span");
+
+        let inner = tracing::info_span!(target: "app", "inner", name = "x");
+        let _inner = inner.enter();
+        tracing::info!(target: "app::inner", "in two spans");
+    }
+    log::info!(target: "dependency::module", "from the log crate {}", 1);
+    log::warn!(target: "dependency", "log warning");
+}
+
+/// Render the events with the given formatters, under a `=== name ===` heading.
+fn render(out: &mut String, name: &str, event: EventFormatter, fields: FieldFormatter, ansi: bool) {
+    let capture = CaptureBuilder::new()
+        .with_event_formatter(event.with_clock(FixedClock::new(fixed_time())))
+        .with_field_formatter(fields)
+        .with_ansi(ansi)
+        .with_echo(false)
+        .start();
+    emit_events();
+
+    writeln!(out, "=== {name} ===").unwrap();
+    for line in capture.lines() {
+        writeln!(out, "{}", line.replace('\x1b', "\\e")).unwrap();
+    }
+    out.push('\n');
+}
+
+/// Render the events with every combination of options.
+fn render_all(ansi: bool) -> String {
+    let mut out = String::new();
+
+    let timestamps = [
+        ("none", TimeFormat::none()),
+        ("utc", TimeFormat::utc()),
+        ("utc_custom", TimeFormat::utc_custom("%H:%M:%S%.3f")),
+    ];
+    for (time_name, time_format) in timestamps {
+        for target in [true, false] {
+            for scope in [true, false] {
+                let formatter = EventFormatter::new()
+                    .with_timestamp(time_format.clone())
+                    .with_target(target)
+                    .with_scope(scope);
+                let name = format!("timestamp={time_name} target={target} scope={scope}");
+                render(&mut out, &name, formatter, FieldFormatter::new(), ansi);
+            }
+        }
+    }
+
+    let placements = [
+        ("declared", FieldFormatter::new()),
+        ("trailing", FieldFormatter::new().with_trailing_fields(true)),
+        ("block", FieldFormatter::new().with_block_fields(Some(1))),
+    ];
+    for layout in [FieldLayout::Brackets, FieldLayout::Logfmt] {
+        for (placement, fields) in &placements {
+            for unquoted in [false, true] {
+                let fields = fields.clone().with_layout(layout).with_unquoted_strings(unquoted);
+                let name = format!("layout={layout:?} fields={placement} unquoted={unquoted}");
+                let formatter = EventFormatter::new().with_timestamp(TimeFormat::none());
+                render(&mut out, &name, formatter, fields, ansi);
+            }
+        }
+    }
+
+    let plain = || EventFormatter::new().with_timestamp(TimeFormat::none());
+    let variants = [
+        (
+            "hidden fields",
+            plain(),
+            FieldFormatter::new()
+                .with_hidden_fields(["count"])
+                .with_hidden_fields_for("app::database", ["token"])
+                .with_only_fields_for("app::inner", ["id"]),
+        ),
+        (
+            "styles",
+            plain(),
+            FieldFormatter::new()
+                .with_key_style(Color::White.normal())
+                .with_value_style(Color::Cyan.normal())
+                .with_number_style(Color::Yellow.bold())
+                .with_bool_style(Color::Magenta.normal())
+                .with_string_style(Color::Green.italic())
+                .with_error_style(Style::new().on(Color::Red)),
+        ),
+        ("format=compact", EventFormatter::compact(), FieldFormatter::new()),
+        (
+            "level labels",
+            plain().with_level_labels(LevelLabels::custom("trc", "dbg", "inf", "wrn", "err")),
+            FieldFormatter::new(),
+        ),
+        (
+            "target width=fixed",
+            plain().with_target_width(TargetWidth::Fixed(16)),
+            FieldFormatter::new(),
+        ),
+        (
+            "target width=adaptive",
+            plain().with_target_width(TargetWidth::Adaptive { max: 24 }),
+            FieldFormatter::new(),
+        ),
+        ("wrap", plain().with_wrap_width(Some(60)), FieldFormatter::new()),
+        (
+            "compact aligned and wrapped",
+            plain()
+                .with_format(Format::Compact)
+                .with_target_width(TargetWidth::Fixed(12))
+                .with_wrap_width(Some(60)),
+            FieldFormatter::new().with_layout(FieldLayout::Logfmt).with_block_fields(Some(1)),
+        ),
+    ];
+    for (name, event, fields) in variants {
+        render(&mut out, name, event, fields, ansi);
+    }
+
+    out
+}
+
+/// Compare `actual` to the snapshot file `name`, or overwrite it if `SERIF_BLESS` is set.
+fn check_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(name);
+    if std::env::var_os("SERIF_BLESS").is_some_and(|v| !v.is_empty()) {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("unable to read snapshot {}: {err}", path.display()));
+    if expected != actual {
+        for (idx, (exp, act)) in expected.lines().zip(actual.lines()).enumerate() {
+            if exp != act {
+                panic!(
+                    "snapshot {name} differs at line {}\nexpected: {exp}\nactual:   {act}\n\
+                     rerun with SERIF_BLESS=1 to update the snapshot if this is intentional",
+                    idx + 1
+                );
+            }
+        }
+        panic!(
+            "snapshot {name} differs in length: expected {} lines, got {}",
+            expected.lines().count(),
+            actual.lines().count()
+        );
+    }
+}
+
+#[test]
+fn plain() {
+    check_snapshot("plain.txt", &render_all(false));
+}
+
+#[test]
+fn ansi() {
+    check_snapshot("ansi.txt", &render_all(true));
+}
+
+#[test]
+fn manual_clock() {
+    let clock = ManualClock::new(fixed_time());
+    let formatter = EventFormatter::new()
+        .with_clock(clock.clone())
+        .with_timestamp(TimeFormat::utc_custom("%H:%M:%S"))
+        .with_target(false);
+    let capture = CaptureBuilder::new().with_event_formatter(formatter).with_echo(false).start();
+
+    tracing::info!("first");
+    clock.advance(Duration::from_secs(75));
+    tracing::info!("second");
+    clock.set("2030-01-01T00:00:00Z".parse().unwrap());
+    tracing::info!("third");
+
+    assert_eq!(
+        capture.lines(),
+        ["13:14:15  INFO first", "13:15:30  INFO second", "00:00:00  INFO third"]
+    );
+}
+
+#[test]
+fn time_format_render() {
+    let ts = fixed_time();
+    assert_eq!(TimeFormat::none().render(ts).to_string(), "");
+    assert_eq!(TimeFormat::utc().render(ts).to_string(), "[2024-02-29T13:14:15Z]");
+    assert_eq!(TimeFormat::utc_custom("%Y/%m/%d %H:%M").render(ts).to_string(), "2024/02/29 13:14");
+    assert_eq!(TimeFormat::utc_custom("%s").render(ts).to_string(), "1709212455");
+    assert_eq!(
+        TimeFormat::utc().render_from(&FixedClock::new(ts)).to_string(),
+        "[2024-02-29T13:14:15Z]"
+    );
+}
+
+#[test]
+fn time_format_debug() {
+    assert_eq!(format!("{:?}", TimeFormat::none()), "TimeFormat::None");
+    assert_eq!(format!("{:?}", TimeFormat::utc()), "TimeFormat::Utc(None)");
+    assert_eq!(format!("{:?}", TimeFormat::local_custom("%H")), "TimeFormat::Local(Some(\"%H\"))");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Unable to use custom TimeFormat")]
+fn time_format_invalid() {
+    let _ = TimeFormat::utc_custom("%J");
+}
@@ -0,0 +1,64 @@
+//! Tests for the end-of-run summary, which install a global subscriber.
+
+use std::{env, fs, process};
+
+use serif::macros::*;
+use serif::{ColorMode, Config, Output, RingBuffer, TimeFormat};
+use tracing::Level;
+use tracing::level_filters::LevelFilter;
+
+#[test]
+fn summary() {
+    assert_eq!(serif::summary(), None);
+
+    let path = env::temp_dir().join(format!("serif-test-{}-summary.log", process::id()));
+    let _ = fs::remove_file(&path);
+    let ring = RingBuffer::new(10);
+    let guard = Config::new()
+        .with_output(Output::File(path.clone()))
+        .with_color(ColorMode::Never)
+        .with_timestamp(TimeFormat::none())
+        .with_log_bridge(false)
+        // a directive for only this target, which doesn't enable serif's own targets
+        .with_env_var("SERIF_TEST_SUMMARY")
+        .with_default(LevelFilter::OFF)
+        .with_target_level("summary", Level::INFO)
+        .with_summary(2)
+        .with_ring_buffer(ring.clone(), Level::WARN)
+        .init();
+
+    info!("starting");
+    warn!("disk almost full");
+    error!("write failed");
+    warn!(retries = 3, "retrying");
+    debug!("not logged");
+
+    let counts = serif::summary().unwrap();
+    assert_eq!(counts.warnings(), 2);
+    assert_eq!(counts.errors(), 1);
+    assert_eq!(counts.get(Level::INFO), 1);
+    assert_eq!(counts.get(Level::DEBUG), 0);
+    assert_eq!(counts.to_string(), "2 warnings, 1 error");
+
+    // the summary was already printed, so dropping the guard doesn't print it again
+    drop(guard);
+
+    let log = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        [
+            " INFO summary: starting",
+            " WARN summary: disk almost full",
+            "ERROR summary: write failed",
+            " WARN summary: retrying [retries=3]",
+            "ERROR serif::summary: 2 warnings, 1 error",
+            "ERROR summary: write failed",
+            " WARN summary: retrying [retries=3]",
+        ]
+    );
+
+    // the ring buffer records the same summary and kept lines as the output
+    let warnings: Vec<_> = log.lines().filter(|line| !line.starts_with(" INFO")).collect();
+    assert_eq!(ring.drain().concat().lines().collect::<Vec<_>>(), warnings);
+}
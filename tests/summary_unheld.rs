@@ -0,0 +1,39 @@
+//! Tests for the end-of-run summary when the guard isn't held, which install a global subscriber.
+
+use std::time::Duration;
+use std::{env, fs, process};
+
+use serif::macros::*;
+use serif::{ColorMode, Config, Output, TimeFormat};
+
+#[test]
+fn summary_unheld_guard() {
+    let path = env::temp_dir().join(format!("serif-test-{}-summary-unheld.log", process::id()));
+    let _ = fs::remove_file(&path);
+    // dropping the guard before anything is logged doesn't use up the summary
+    let _ = Config::new()
+        .with_output(Output::File(path.clone()))
+        .with_color(ColorMode::Always)
+        .with_timestamp(TimeFormat::none())
+        .with_log_bridge(false)
+        .with_dedup(Duration::from_secs(3600))
+        .with_summary(1)
+        .init();
+
+    // suppressed events are counted too
+    for _ in 0..5 {
+        warn!("disk almost full");
+    }
+
+    let counts = serif::summary().unwrap();
+    assert_eq!(counts.warnings(), 5);
+
+    let log = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    // the kept line is a copy of the first one without colors
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), 3, "{lines:?}");
+    assert!(lines[0].contains("\x1b[") && lines[0].ends_with("disk almost full"), "{lines:?}");
+    assert!(lines[1].contains("5 warnings, 0 errors"), "{lines:?}");
+    assert_eq!(lines[2], " WARN summary_unheld: disk almost full");
+}
@@ -0,0 +1,69 @@
+//! Tests for duplicate suppression and rate limiting, which install a global subscriber.
+
+use std::time::Duration;
+use std::{env, fs, process, thread};
+
+use serif::macros::*;
+use serif::{ColorMode, Config, Output, TimeFormat};
+use tracing::Level;
+use tracing::level_filters::LevelFilter;
+
+#[test]
+fn dedup_and_rate_limit() {
+    let path = env::temp_dir().join(format!("serif-test-{}-suppress.log", process::id()));
+    let _ = fs::remove_file(&path);
+    let guard = Config::new()
+        .with_output(Output::File(path.clone()))
+        .with_color(ColorMode::Never)
+        .with_timestamp(TimeFormat::none())
+        .with_log_bridge(false)
+        // a directive for only this target, which doesn't enable serif's own targets
+        .with_env_var("SERIF_TEST_SUPPRESS")
+        .with_default(LevelFilter::OFF)
+        .with_target_level("suppress", Level::INFO)
+        .with_dedup(Duration::from_secs(3600))
+        .with_rate_limit(2, Duration::from_millis(200))
+        .init();
+
+    // identical events are collapsed until the callsite logs something else
+    for msg in ["disk full", "disk full", "disk full", "disk ok"] {
+        warn!("{msg}");
+    }
+
+    // let the rate limit recover, then exceed it
+    thread::sleep(Duration::from_millis(250));
+    for i in 0..5 {
+        info!("request {i}");
+    }
+    thread::sleep(Duration::from_millis(250));
+    info!("request 5");
+
+    // pending summaries are written when the guard is dropped
+    for _ in 0..3 {
+        warn!("shutting down");
+    }
+    drop(guard);
+
+    let log = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        [
+            " WARN suppress: disk full",
+            " WARN suppress: (repeated 2 times)",
+            " WARN suppress: disk ok",
+            " INFO suppress: request 0",
+            " INFO suppress: request 1",
+            " INFO suppress: (3 events dropped by rate limit)",
+            " INFO suppress: request 5",
+            " WARN suppress: shutting down",
+            " WARN suppress: (repeated 2 times)",
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Rate limit burst must be non-zero")]
+fn rate_limit_zero_burst() {
+    let _ = Config::new().with_rate_limit(0, Duration::from_secs(1));
+}
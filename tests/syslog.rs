@@ -0,0 +1,95 @@
+//! Tests for syslog output, using a local socket in place of `/dev/log`.
+#![cfg(unix)]
+
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+use serif::macros::*;
+use serif::{EventFormatter, Facility, Syslog, SyslogFormat, TimeFormat};
+
+/// A bound socket standing in for the syslog daemon, which is removed when dropped.
+struct Daemon {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl Daemon {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("serif-test-{}-{name}.sock", process::id()));
+        let _ = fs::remove_file(&path);
+        let socket = UnixDatagram::bind(&path).unwrap();
+        Self { socket, path }
+    }
+
+    fn recv(&self) -> String {
+        let mut buf = [0u8; 4096];
+        let len = self.socket.recv(&mut buf).unwrap();
+        String::from_utf8(buf[..len].to_vec()).unwrap()
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn log_to(syslog: &Syslog, f: impl FnOnce()) {
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(syslog.connect().unwrap())
+        .with_ansi(false)
+        .with_max_level(serif::tracing::Level::TRACE)
+        .event_format(EventFormatter::new().with_timestamp(TimeFormat::none()).with_target(false))
+        .finish();
+    serif::tracing::subscriber::with_default(subscriber, f);
+}
+
+#[test]
+fn rfc5424() {
+    let daemon = Daemon::new("5424");
+    let syslog = Syslog::new()
+        .with_path(&daemon.path)
+        .with_facility(Facility::Local3)
+        .with_app_name("my app");
+
+    log_to(&syslog, || {
+        warn!(answer = 42, "hello");
+        trace!("details");
+    });
+
+    let header = format!(" - my_app {} - - ", process::id());
+    let msg = daemon.recv();
+    // Local3 (19) * 8 + warning (4)
+    assert!(msg.starts_with("<156>1 20"), "{msg}");
+    let (timestamp, rest) = msg["<156>1 ".len()..].split_once(' ').unwrap();
+    assert!(timestamp.parse::<jiff::Timestamp>().is_ok(), "{msg}");
+    assert_eq!(format!(" {rest}"), format!("{header} WARN hello answer=42"));
+
+    let msg = daemon.recv();
+    assert!(msg.starts_with("<159>1 "), "{msg}");
+    assert!(msg.ends_with(&format!("{header}TRACE details")), "{msg}");
+}
+
+#[test]
+fn rfc3164() {
+    let daemon = Daemon::new("3164");
+    let syslog = Syslog::new()
+        .with_path(&daemon.path)
+        .with_format(SyslogFormat::Rfc3164)
+        .with_app_name("myapp");
+
+    log_to(&syslog, || error!("oh no"));
+
+    let msg = daemon.recv();
+    // User (1) * 8 + err (3), then a timestamp like "Feb 29 13:14:15"
+    assert!(msg.starts_with("<11>"), "{msg}");
+    assert_eq!(msg.as_bytes()[4 + 15], b' ', "{msg}");
+    assert_eq!(&msg[4 + 16..], format!("myapp[{}]: ERROR oh no", process::id()));
+}
+
+#[test]
+fn connect_error() {
+    let syslog = Syslog::new().with_path(env::temp_dir().join("serif-test-missing.sock"));
+    assert!(syslog.connect().is_err());
+}
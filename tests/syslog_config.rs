@@ -0,0 +1,30 @@
+//! Tests for syslog output through `Config::init`, which installs a global subscriber.
+#![cfg(unix)]
+
+use std::os::unix::net::UnixDatagram;
+use std::{env, fs, process};
+
+use serif::macros::*;
+use serif::{ColorMode, Config, Output, Syslog, TimeFormat};
+
+#[test]
+fn syslog_never_uses_ansi() {
+    let path = env::temp_dir().join(format!("serif-test-{}-config.sock", process::id()));
+    let _ = fs::remove_file(&path);
+    let daemon = UnixDatagram::bind(&path).unwrap();
+
+    let _guard = Config::new()
+        .with_output(Output::Syslog(Syslog::new().with_path(&path).with_app_name("app")))
+        .with_color(ColorMode::Always)
+        .with_timestamp(TimeFormat::none())
+        .with_log_bridge(false)
+        .init();
+    warn!(answer = 42, "hello");
+
+    let mut buf = [0u8; 4096];
+    let len = daemon.recv(&mut buf).unwrap();
+    let _ = fs::remove_file(&path);
+    let message = String::from_utf8(buf[..len].to_vec()).unwrap();
+    assert!(message.ends_with(" WARN syslog_config: hello [answer=42]"), "{message:?}");
+    assert!(!message.contains('\x1b'), "{message:?}");
+}
@@ -0,0 +1,103 @@
+//! Tests for the `serif::testing` capture helpers.
+
+use serif::testing::{self, CaptureBuilder, EventMatcher};
+use serif::{FieldFormatter, FieldLayout};
+use tracing::Level;
+
+#[test]
+fn captures_lines_and_events() {
+    let capture = testing::capture();
+    tracing::info!(target: "app::db", rows = 3, table = "users", "query finished");
+    tracing::debug!(target: "app::http", "request");
+
+    assert_eq!(
+        capture.lines(),
+        [" INFO app::db: query finished [rows=3][table=\"users\"]", "DEBUG app::http: request"]
+    );
+
+    let events = capture.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].level(), Level::INFO);
+    assert_eq!(events[0].target(), "app::db");
+    assert_eq!(events[0].message(), "query finished");
+    assert_eq!(events[0].field("rows"), Some("3"));
+    assert_eq!(events[0].field("table"), Some("users"));
+    assert_eq!(events[0].field("missing"), None);
+    assert_eq!(events[0].output(), " INFO app::db: query finished [rows=3][table=\"users\"]");
+}
+
+#[test]
+fn matchers() {
+    let capture = testing::capture();
+    tracing::warn!(target: "app::auth", user = "bob", "login failed");
+    tracing::warn!(target: "app::auth", user = "alice", "login failed");
+    tracing::info!(target: "app::auth", user = "alice", "login succeeded");
+    tracing::info!(target: "application", "unrelated");
+
+    let failed = EventMatcher::new().with_level(Level::WARN).with_message("failed");
+    assert_eq!(capture.count(&failed), 2);
+    assert_eq!(capture.count(&failed.clone().with_field("user", "alice")), 1);
+    assert_eq!(capture.count(&EventMatcher::new().with_target("app")), 3);
+    assert_eq!(capture.count(&EventMatcher::new().with_target("app::auth")), 3);
+    assert_eq!(capture.count(&EventMatcher::new().with_target("app::au")), 0);
+    assert_eq!(capture.count(&EventMatcher::new().with_target("other")), 0);
+
+    let found = capture.find(&EventMatcher::new().with_level(Level::INFO)).unwrap();
+    assert_eq!(found.message(), "login succeeded");
+
+    capture.assert_logged(&failed.with_field("user", "bob"));
+    capture.assert_not_logged(&EventMatcher::new().with_level(Level::ERROR));
+
+    capture.clear();
+    assert!(capture.lines().is_empty());
+    assert!(capture.events().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "captured lines:\n     WARN app: something else")]
+fn assert_logged_failure() {
+    let capture = testing::capture();
+    tracing::warn!(target: "app", "something else");
+    capture.assert_logged(&EventMatcher::new().with_message("expected"));
+}
+
+#[test]
+fn builder_options() {
+    let capture = CaptureBuilder::new()
+        .with_default(Level::INFO)
+        .with_field_formatter(FieldFormatter::new().with_layout(FieldLayout::Logfmt))
+        .with_ansi(true)
+        .with_echo(false)
+        .start();
+    tracing::debug!(target: "app", "filtered out");
+    tracing::info!(target: "app", n = 1, "kept");
+
+    assert_eq!(capture.events().len(), 1);
+    assert_eq!(
+        capture.lines(),
+        ["\x1b[32m INFO \x1b[0m\x1b[2;34mapp\x1b[0m: kept\x1b[2m n=1\x1b[0m"]
+    );
+}
+
+#[test]
+fn capture_is_thread_local() {
+    let capture = testing::capture();
+    std::thread::spawn(|| tracing::info!("on another thread")).join().unwrap();
+    tracing::info!(target: "app", "on this thread");
+
+    assert_eq!(capture.lines(), [" INFO app: on this thread"]);
+}
+
+#[test]
+fn capture_stops_when_dropped() {
+    let first = testing::capture();
+    tracing::info!(target: "app", "first");
+    {
+        let second = testing::capture();
+        tracing::info!(target: "app", "second");
+        assert_eq!(second.lines(), [" INFO app: second"]);
+    }
+    tracing::info!(target: "app", "third");
+
+    assert_eq!(first.lines(), [" INFO app: first", " INFO app: third"]);
+}